@@ -0,0 +1,301 @@
+//! The `Adsr` component is an envelope generator: after `gate_on()` the output rises from its current level to 1.0
+//! during `attack`, falls to `sustain` during `decay`, stays at `sustain` until `gate_off()`, then falls to 0.0 during `release`
+//! (times in seconds). Multiplied with an oscillator (e.g. in a `Graph`), it shapes the notes over time.
+//!
+//! The stages are linear (`AdsrCurve::Linear`) or exponential (`AdsrCurve::Exponential`, one-pole curves aiming beyond
+//! the end of the stage, like analog envelopes). In both cases, each stage lasts exactly its time.
+//!
+//! `AdsrMode::Retrigger` restarts the attack on every `gate_on()`, `AdsrMode::Legato` ignores a `gate_on()`
+//! while the gate is already on (overlapping notes), so the envelope continues.
+//! With `set_gate_input(true)`, the input of `tick()` is the gate (on while > 0.0), e.g. from a `Pwm` or a sequencer.
+//!
+//! # Examples:
+//! Linear envelope at 1kHz: attack 10 frames, decay 20 frames to 0.5, release 50 frames:
+//! ```rust
+//! # use mydsp_jack::adsr::{Adsr, AdsrStage};
+//! # use mydsp_jack::AudioComponent;
+//! let mut adsr1 = Adsr::new(1000, 0.01, 0.02, 0.5, 0.05);
+//! adsr1.gate_on();
+//! let envelope: Vec<f32> = (0..100).map(|_| adsr1.tick(0.0)).collect();
+//! debug_assert!((envelope[4] - 0.5).abs() < 1e-6); // 5 / 10 of the attack
+//! debug_assert_eq!(envelope[9], 1.0);
+//! debug_assert!((envelope[19] - 0.75).abs() < 1e-6); // 10 / 20 of the decay
+//! debug_assert_eq!(envelope[29], 0.5);
+//! debug_assert_eq!(adsr1.stage(), AdsrStage::Sustain);
+//! adsr1.gate_off();
+//! let release: Vec<f32> = (0..60).map(|_| adsr1.tick(0.0)).collect();
+//! debug_assert!((release[24] - 0.25).abs() < 1e-6);
+//! debug_assert_eq!(release[49], 0.0);
+//! debug_assert_eq!(adsr1.stage(), AdsrStage::Idle);
+//! ```
+//! A `Pwm` (on for 50 frames every 100 frames) used as the gate retriggers the envelope:
+//! ```rust
+//! # use mydsp_jack::adsr::Adsr;
+//! # use mydsp_jack::pwm::Pwm;
+//! # use mydsp_jack::AudioComponent;
+//! let mut pwm1 = Pwm::new(0.5, 100);
+//! let mut adsr2 = Adsr::new(1000, 0.01, 0.01, 0.5, 0.02);
+//! adsr2.set_gate_input(true);
+//! let envelope: Vec<f32> = (0..200).map(|_| adsr2.tick(pwm1.tick(0.0))).collect();
+//! for note in [0, 100] {
+//!     debug_assert_eq!(envelope[note + 9], 1.0); // end of the attack
+//!     debug_assert_eq!(envelope[note + 49], 0.5); // sustain
+//!     debug_assert_eq!(envelope[note + 69], 0.0); // end of the release
+//! }
+//! ```
+use crate::AudioComponent;
+
+/// Overshoot of the target of the exponential attack (relative to the stage height), smaller -> more curved.
+const ATTACK_RATIO: f32 = 0.3;
+/// Overshoot of the target of the exponential decay and release (relative to the stage height).
+const DECAY_RATIO: f32 = 0.001;
+
+/// Shape of the stages of an `Adsr`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AdsrCurve {
+    #[default]
+    Linear,
+    /// Fast start and slow end of each stage.
+    Exponential,
+}
+
+/// Behaviour of an `Adsr` when the gate is turned on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AdsrMode {
+    /// Every `gate_on()` restarts the attack (from the current level, no click).
+    #[default]
+    Retrigger,
+    /// A `gate_on()` while the gate is already on does not restart the envelope.
+    Legato,
+}
+
+/// Current stage of an `Adsr`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdsrStage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+#[derive(Debug)]
+pub struct Adsr {
+    sample_rate: usize,
+    attack: f32, // seconds
+    decay: f32,
+    sustain: f32,
+    release: f32,
+    curve: AdsrCurve,
+    mode: AdsrMode,
+    gate_input: bool, // the input of `tick()` is the gate.
+    gate: bool,
+    stage: AdsrStage,
+    level: f32,
+    end: f32, // level at the end of the current stage.
+    remaining: usize, // frames until the end of the current stage.
+    coefficient: f32, // level = base + coefficient * level.
+    base: f32,
+}
+
+impl Adsr {
+    /// Returns an idle `Adsr` instance with `attack`, `decay` and `release` times (seconds) and a `sustain` level in [0.0, 1.0].
+    ///
+    /// Default: linear curves, retrigger mode, gate controlled by `gate_on()` / `gate_off()`.
+    ///
+    /// # Panics
+    /// The function panics if a time is negative or if `sustain` is not in the range [0.0, 1.0].
+    /// ```rust, should_panic
+    /// # use mydsp_jack::adsr::Adsr;
+    /// let adsr_panic = Adsr::new(48000, 0.01, 0.1, 1.5, 0.2);
+    /// ```
+    pub fn new(sample_rate: usize, attack: f32, decay: f32, sustain: f32, release: f32) -> Self {
+        let mut adsr = Adsr {
+            sample_rate,
+            attack: 0.0,
+            decay: 0.0,
+            sustain: 0.0,
+            release: 0.0,
+            curve: AdsrCurve::default(),
+            mode: AdsrMode::default(),
+            gate_input: false,
+            gate: false,
+            stage: AdsrStage::Idle,
+            level: 0.0,
+            end: 0.0,
+            remaining: 0,
+            coefficient: 1.0,
+            base: 0.0,
+        };
+        adsr.set_attack(attack);
+        adsr.set_decay(decay);
+        adsr.set_sustain(sustain);
+        adsr.set_release(release);
+        adsr
+    }
+    /// Modifies the attack time (seconds), used from the next attack.
+    pub fn set_attack(&mut self, attack: f32) {
+        self.attack = check_time(attack);
+    }
+    /// Modifies the decay time (seconds), used from the next decay.
+    pub fn set_decay(&mut self, decay: f32) {
+        self.decay = check_time(decay);
+    }
+    /// Modifies the sustain level in [0.0, 1.0], used from the next decay (immediately during the sustain).
+    pub fn set_sustain(&mut self, sustain: f32) {
+        if !(0.0..=1.0).contains(&sustain) {
+            panic!("!!!Adsr panic: sustain must be in the range [0.0, 1.0]");
+        }
+        self.sustain = sustain;
+        if self.stage == AdsrStage::Sustain {
+            self.level = sustain;
+        }
+    }
+    /// Modifies the release time (seconds), used from the next release.
+    pub fn set_release(&mut self, release: f32) {
+        self.release = check_time(release);
+    }
+    /// Modifies the shape of the stages, used from the next stage.
+    ///
+    /// # Examples:
+    /// The exponential attack rises faster at the beginning and the exponential decay falls faster at the beginning,
+    /// with the same durations:
+    /// ```rust
+    /// # use mydsp_jack::adsr::{Adsr, AdsrCurve};
+    /// # use mydsp_jack::AudioComponent;
+    /// let envelope = |curve: AdsrCurve| {
+    ///     let mut adsr3 = Adsr::new(1000, 0.01, 0.02, 0.5, 0.05);
+    ///     adsr3.set_curve(curve);
+    ///     adsr3.gate_on();
+    ///     (0..40).map(|_| adsr3.tick(0.0)).collect::<Vec<f32>>()
+    /// };
+    /// let (linear, exponential) = (envelope(AdsrCurve::Linear), envelope(AdsrCurve::Exponential));
+    /// debug_assert!(exponential[4] > linear[4] + 0.1);
+    /// debug_assert!(exponential[19] < linear[19] - 0.1);
+    /// debug_assert!((exponential[9] - 1.0).abs() < 1e-6 && exponential[29] == 0.5);
+    /// ```
+    pub fn set_curve(&mut self, curve: AdsrCurve) {
+        self.curve = curve;
+    }
+    /// Modifies the behaviour when the gate is turned on.
+    ///
+    /// # Examples:
+    /// A second `gate_on()` during the decay restarts the attack in retrigger mode, not in legato mode:
+    /// ```rust
+    /// # use mydsp_jack::adsr::{Adsr, AdsrMode, AdsrStage};
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut adsr4 = Adsr::new(1000, 0.01, 0.02, 0.5, 0.05);
+    /// adsr4.set_mode(AdsrMode::Legato);
+    /// adsr4.gate_on();
+    /// (0..15).for_each(|_| { adsr4.tick(0.0); });
+    /// adsr4.gate_on(); // overlapping note
+    /// debug_assert_eq!(adsr4.stage(), AdsrStage::Decay);
+    /// adsr4.set_mode(AdsrMode::Retrigger);
+    /// adsr4.gate_on();
+    /// debug_assert_eq!(adsr4.stage(), AdsrStage::Attack);
+    /// ```
+    pub fn set_mode(&mut self, mode: AdsrMode) {
+        self.mode = mode;
+    }
+    /// Uses the input of `tick()` as the gate (on while > 0.0) if `gate_input` is `true`.
+    pub fn set_gate_input(&mut self, gate_input: bool) {
+        self.gate_input = gate_input;
+    }
+    /// Turns the gate on: starts the attack (see `AdsrMode`).
+    pub fn gate_on(&mut self) {
+        if !(self.gate && self.mode == AdsrMode::Legato) {
+            self.start_stage(AdsrStage::Attack);
+        }
+        self.gate = true;
+    }
+    /// Turns the gate off: starts the release from the current level.
+    pub fn gate_off(&mut self) {
+        if self.gate && self.stage != AdsrStage::Idle {
+            self.start_stage(AdsrStage::Release);
+        }
+        self.gate = false;
+    }
+    /// Returns the current stage.
+    pub fn stage(&self) -> AdsrStage {
+        self.stage
+    }
+    /// Returns `true` if the envelope is not idle.
+    pub fn is_active(&self) -> bool {
+        self.stage != AdsrStage::Idle
+    }
+    /// Returns the current level (last output).
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+    /// Returns to the idle stage at level 0.0.
+    pub fn reset(&mut self) {
+        self.gate = false;
+        self.stage = AdsrStage::Idle;
+        self.level = 0.0;
+    }
+    /// Moves to `stage` from the current level, skipping the stages of zero length.
+    fn start_stage(&mut self, stage: AdsrStage) {
+        let (end, time, ratio) = match stage {
+            AdsrStage::Attack => (1.0, self.attack, ATTACK_RATIO),
+            AdsrStage::Decay => (self.sustain, self.decay, DECAY_RATIO),
+            AdsrStage::Release => (0.0, self.release, DECAY_RATIO),
+            AdsrStage::Sustain | AdsrStage::Idle => {
+                self.stage = stage;
+                self.level = if stage == AdsrStage::Sustain { self.sustain } else { 0.0 };
+                return;
+            }
+        };
+        let nframes = (time * self.sample_rate as f32).round();
+        if nframes < 1.0 || self.level == end {
+            self.level = end;
+            self.start_stage(next_stage(stage));
+            return;
+        }
+        (self.coefficient, self.base) = match self.curve {
+            AdsrCurve::Linear => (1.0, (end - self.level) / nframes),
+            AdsrCurve::Exponential => {
+                // the curve aims at `target` beyond `end` and reaches `end` after `nframes`.
+                let target = end + ratio * (end - self.level);
+                let coefficient = (ratio / (1.0 + ratio)).powf(1.0 / nframes);
+                (coefficient, target * (1.0 - coefficient))
+            }
+        };
+        self.stage = stage;
+        self.end = end;
+        self.remaining = nframes as usize;
+    }
+}
+
+/// Returns `time` if it is not negative.
+fn check_time(time: f32) -> f32 {
+    if time < 0.0 {
+        panic!("!!!Adsr panic: times must be non-negative");
+    }
+    time
+}
+
+/// Returns the stage following `stage` (the sustain and idle stages last until a gate change).
+fn next_stage(stage: AdsrStage) -> AdsrStage {
+    match stage {
+        AdsrStage::Attack => AdsrStage::Decay,
+        AdsrStage::Decay | AdsrStage::Sustain => AdsrStage::Sustain,
+        AdsrStage::Release | AdsrStage::Idle => AdsrStage::Idle,
+    }
+}
+
+impl AudioComponent for Adsr {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        if self.gate_input && (in_frame > 0.0) != self.gate {
+            if self.gate { self.gate_off() } else { self.gate_on() }
+        }
+        if matches!(self.stage, AdsrStage::Attack | AdsrStage::Decay | AdsrStage::Release) {
+            self.level = self.base + self.coefficient * self.level;
+            self.remaining -= 1;
+            if self.remaining == 0 {
+                self.level = self.end; // no rounding error at the end of the stage.
+                self.start_stage(next_stage(self.stage));
+            }
+        }
+        self.level
+    }
+}
@@ -0,0 +1,66 @@
+//! The `Allpass` component is a Schroeder allpass filter built on a fixed `Delay`:
+//! a flat magnitude response with a dense phase response, which diffuses the echoes of a reverb.
+//!
+//! v = input + gain * v delayed by nframes_delay,
+//! output = v delayed by nframes_delay - gain * v
+//! (i.e. output = -gain * input + input delayed + gain * output delayed, with a single delay line).
+//!
+//! # Examples:
+//! Impulse response: -gain, then 1 - gain^2 after nframes_delay, then decaying by `gain` every nframes_delay,
+//! with a total energy of 1 (lossless):
+//! ```rust
+//! # use mydsp_jack::allpass::Allpass;
+//! # use mydsp_jack::AudioComponent;
+//! let mut allpass1 = Allpass::new(3, 0.5);
+//! let response: Vec<f32> = (0..300).map(|n| allpass1.tick(if n == 0 { 1.0 } else { 0.0 })).collect();
+//! debug_assert_eq!(response[0..7], [-0.5, 0.0, 0.0, 0.75, 0.0, 0.0, 0.375]);
+//! let energy: f32 = response.iter().map(|x| x * x).sum();
+//! debug_assert!((energy - 1.0).abs() < 1e-6);
+//! ```
+use crate::AudioComponent;
+use crate::delay::Delay;
+
+#[derive(Debug)]
+pub struct Allpass {
+    delay: Delay,
+    gain: f32,
+}
+
+impl Allpass {
+    /// Returns an `Allpass` instance with a delay of `nframes_delay` frames and a `gain` in (-1.0, 1.0).
+    ///
+    /// # Panics
+    /// The function panics if `nframes_delay` is not a positive integer or if `gain` is not in the range (-1.0, 1.0).
+    /// ```rust, should_panic
+    /// # use mydsp_jack::allpass::Allpass;
+    /// let allpass_panic = Allpass::new(100, 1.5);
+    /// ```
+    pub fn new(nframes_delay: usize, gain: f32) -> Self {
+        let mut allpass = Allpass {
+            delay: Delay::new(nframes_delay),
+            gain: 0.0,
+        };
+        allpass.set_gain(gain);
+        allpass
+    }
+    /// Modifies the gain in (-1.0, 1.0).
+    pub fn set_gain(&mut self, gain: f32) {
+        if gain <= -1.0 || gain >= 1.0 {
+            panic!("!!!Allpass panic: gain must be in the range (-1.0, 1.0)");
+        }
+        self.gain = gain;
+    }
+    /// Clears the delay line.
+    pub fn reset(&mut self) {
+        self.delay.reset();
+    }
+}
+
+impl AudioComponent for Allpass {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        let delayed_frame = self.delay.read();
+        let v = in_frame + self.gain * delayed_frame;
+        self.delay.tick(v);
+        delayed_frame - self.gain * v
+    }
+}
@@ -14,8 +14,8 @@ pub struct Am {
 impl Am {
     pub fn new(sample_rate: usize, sine_table: &'static SineTable, fc: f32, fm: f32, mod_index: f32, gain: f32) -> Self {
         Am {
-            c_phasor: Phasor::new(0.0, fc / sample_rate as f32),
-            m_phasor: Phasor::new(0.0, fm / sample_rate as f32),
+            c_phasor: Phasor::with_frequency(sample_rate, fc),
+            m_phasor: Phasor::with_frequency(sample_rate, fm),
             mod_index,
             gain,
             sine_table,
@@ -0,0 +1,245 @@
+//! The `Biquad` component is a second-order IIR filter implementing the designs of the
+//! [Audio EQ Cookbook (Robert Bristow-Johnson)](https://www.w3.org/TR/audio-eq-cookbook/):
+//! low-pass, high-pass, band-pass, notch, all-pass, peaking, low-shelf and high-shelf,
+//! configured with a frequency (Hz), a quality factor `q` and a gain (dB, peaking and shelves only).
+//!
+//! The filter uses the transposed direct form II, and the parameters set by `set_frequency()`, `set_q()` and `set_gain()`
+//! are smoothed per sample (one-pole, as in `Smooth`) before recomputing the coefficients,
+//! so they can be modulated without clicks.
+//!
+//! # Examples:
+//! Magnitude responses at characteristic frequencies:
+//! ```rust
+//! # use mydsp_jack::biquad::{Biquad, BiquadType};
+//! let q = std::f32::consts::FRAC_1_SQRT_2; // Butterworth
+//! let lowpass = Biquad::new(48000, BiquadType::LowPass, 1000.0, q, 0.0);
+//! debug_assert!((lowpass.magnitude_db(1000.0) + 3.01).abs() < 0.05); // -3dB at the cutoff frequency
+//! debug_assert!(lowpass.magnitude_db(10.0).abs() < 0.01);
+//! debug_assert!((lowpass.magnitude_db(10000.0) + 40.0).abs() < 3.0); // -12dB per octave
+//! let highpass = Biquad::new(48000, BiquadType::HighPass, 1000.0, q, 0.0);
+//! debug_assert!((highpass.magnitude_db(1000.0) + 3.01).abs() < 0.05);
+//! debug_assert!(highpass.magnitude_db(20000.0).abs() < 0.1);
+//! let bandpass = Biquad::new(48000, BiquadType::BandPass, 1000.0, 2.0, 0.0);
+//! debug_assert!(bandpass.magnitude_db(1000.0).abs() < 0.01); // 0dB peak gain
+//! debug_assert!(bandpass.magnitude_db(100.0) < -20.0);
+//! let notch = Biquad::new(48000, BiquadType::Notch, 1000.0, 2.0, 0.0);
+//! debug_assert!(notch.magnitude_db(1000.0) < -60.0);
+//! debug_assert!(notch.magnitude_db(100.0).abs() < 0.1);
+//! let allpass = Biquad::new(48000, BiquadType::AllPass, 1000.0, 2.0, 0.0);
+//! for frequency in [20.0, 1000.0, 15000.0] { debug_assert!(allpass.magnitude_db(frequency).abs() < 0.01); }
+//! let peaking = Biquad::new(48000, BiquadType::Peaking, 1000.0, 1.0, 6.0);
+//! debug_assert!((peaking.magnitude_db(1000.0) - 6.0).abs() < 0.01);
+//! debug_assert!(peaking.magnitude_db(20.0).abs() < 0.1);
+//! let low_shelf = Biquad::new(48000, BiquadType::LowShelf, 1000.0, q, -6.0);
+//! debug_assert!((low_shelf.magnitude_db(10.0) + 6.0).abs() < 0.05);
+//! debug_assert!((low_shelf.magnitude_db(1000.0) + 3.0).abs() < 0.05); // half gain at the shelf frequency
+//! debug_assert!(low_shelf.magnitude_db(20000.0).abs() < 0.1);
+//! let high_shelf = Biquad::new(48000, BiquadType::HighShelf, 1000.0, q, 6.0);
+//! debug_assert!(high_shelf.magnitude_db(10.0).abs() < 0.05);
+//! debug_assert!((high_shelf.magnitude_db(20000.0) - 6.0).abs() < 0.1);
+//! ```
+//! The measured amplitude of a sinusoid matches the magnitude response:
+//! ```rust
+//! # use mydsp_jack::biquad::{Biquad, BiquadType};
+//! # use mydsp_jack::AudioComponent;
+//! let mut lowpass = Biquad::new(48000, BiquadType::LowPass, 500.0, 0.707, 0.0);
+//! let mut amplitude = 0.0_f32;
+//! for n in 0..48000 {
+//!     let out_frame = lowpass.tick((2.0 * std::f32::consts::PI * 2000.0 * n as f32 / 48000.0).sin());
+//!     if n > 24000 { amplitude = amplitude.max(out_frame.abs()); } // steady state
+//! }
+//! debug_assert!((20.0 * amplitude.log10() - lowpass.magnitude_db(2000.0)).abs() < 0.1);
+//! ```
+use std::f32::consts::PI;
+use crate::AudioComponent;
+
+/// Responses of the Audio EQ Cookbook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BiquadType {
+    LowPass,
+    HighPass,
+    /// Band-pass with a constant 0dB peak gain.
+    BandPass,
+    Notch,
+    AllPass,
+    /// Peaking EQ, boost or cut of `gain` dB around the frequency.
+    Peaking,
+    /// Boost or cut of `gain` dB below the frequency.
+    LowShelf,
+    /// Boost or cut of `gain` dB above the frequency.
+    HighShelf,
+}
+
+/// Time constant of the parameter smoothing (seconds).
+const SMOOTHING_TIME: f32 = 0.005;
+
+#[derive(Debug)]
+pub struct Biquad {
+    filter_type: BiquadType,
+    sample_rate: usize,
+    target: [f32; 3], // frequency, q, gain (dB) set by the user.
+    current: [f32; 3], // smoothed frequency, q, gain (dB) used by the coefficients.
+    smoothing_factor: f32,
+    b: [f32; 3], // feedforward coefficients (normalized by a0).
+    a: [f32; 2], // feedback coefficients a1, a2 (normalized by a0).
+    state: [f32; 2], // transposed direct form II.
+}
+
+impl Biquad {
+    /// Returns a `Biquad` instance of a given type, `frequency` (Hz), quality factor `q` and `gain` (dB, ignored by
+    /// low-pass, high-pass, band-pass, notch and all-pass).
+    ///
+    /// # Panics
+    /// The function panics if `frequency` is not in the range (0, sample_rate / 2), if `q` is not positive
+    /// or if `gain` is not finite (NaN included), the setters too.
+    /// ```rust, should_panic
+    /// # use mydsp_jack::biquad::{Biquad, BiquadType};
+    /// let biquad_panic = Biquad::new(48000, BiquadType::LowPass, 30000.0, 0.707, 0.0);
+    /// ```
+    /// ```rust, should_panic
+    /// # use mydsp_jack::biquad::{Biquad, BiquadType};
+    /// let mut biquad_nan = Biquad::new(48000, BiquadType::Peaking, 1000.0, 0.707, 6.0);
+    /// biquad_nan.set_frequency(f32::NAN);
+    /// ```
+    pub fn new(sample_rate: usize, filter_type: BiquadType, frequency: f32, q: f32, gain: f32) -> Self {
+        let mut biquad = Biquad {
+            filter_type,
+            sample_rate,
+            target: [frequency, q, gain],
+            current: [frequency, q, gain],
+            smoothing_factor: (-1.0 / (SMOOTHING_TIME * sample_rate as f32)).exp(),
+            b: [1.0, 0.0, 0.0],
+            a: [0.0, 0.0],
+            state: [0.0; 2],
+        };
+        biquad.check_parameters(frequency, q, gain);
+        biquad.update_coefficients();
+        biquad
+    }
+    /// Modifies the type of the filter (immediately, not smoothed).
+    pub fn set_type(&mut self, filter_type: BiquadType) {
+        self.filter_type = filter_type;
+        self.update_coefficients();
+    }
+    /// Modifies the frequency (Hz), smoothed per sample.
+    ///
+    /// # Examples:
+    /// Jumping from 200Hz to 8000Hz while filtering a 100Hz sinusoid does not click
+    /// (without smoothing, the phase shift of the low-pass would make the output jump by about 0.5):
+    /// ```rust
+    /// # use mydsp_jack::biquad::{Biquad, BiquadType};
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut lowpass = Biquad::new(48000, BiquadType::LowPass, 200.0, 0.707, 0.0);
+    /// let mut previous_frame = 0.0;
+    /// let mut max_step = 0.0_f32;
+    /// for n in 0..9600 {
+    ///     if n == 4800 { lowpass.set_frequency(8000.0); }
+    ///     let out_frame = lowpass.tick((2.0 * std::f32::consts::PI * 100.0 * n as f32 / 48000.0).sin());
+    ///     max_step = max_step.max((out_frame - previous_frame).abs());
+    ///     previous_frame = out_frame;
+    /// }
+    /// debug_assert!(max_step < 0.1); // about 0.04, the input moves by up to 0.013 per frame
+    /// ```
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.check_parameters(frequency, self.target[1], self.target[2]);
+        self.target[0] = frequency;
+    }
+    /// Modifies the quality factor, smoothed per sample.
+    pub fn set_q(&mut self, q: f32) {
+        self.check_parameters(self.target[0], q, self.target[2]);
+        self.target[1] = q;
+    }
+    /// Modifies the gain (dB) of peaking and shelves, smoothed per sample.
+    pub fn set_gain(&mut self, gain: f32) {
+        self.check_parameters(self.target[0], self.target[1], gain);
+        self.target[2] = gain;
+    }
+    /// Clears the state of the filter (not the parameters).
+    pub fn reset(&mut self) {
+        self.state = [0.0; 2];
+    }
+    /// Returns the magnitude response (linear) of the current coefficients at `frequency` (Hz).
+    pub fn magnitude(&self, frequency: f32) -> f32 {
+        let w = 2.0 * PI * frequency / self.sample_rate as f32;
+        // H(e^jw) = (b0 + b1 e^-jw + b2 e^-2jw) / (1 + a1 e^-jw + a2 e^-2jw)
+        let (c1, s1, c2, s2) = (w.cos(), w.sin(), (2.0 * w).cos(), (2.0 * w).sin());
+        let numerator = (self.b[0] + self.b[1] * c1 + self.b[2] * c2, self.b[1] * s1 + self.b[2] * s2);
+        let denominator = (1.0 + self.a[0] * c1 + self.a[1] * c2, self.a[0] * s1 + self.a[1] * s2);
+        (numerator.0.hypot(numerator.1)) / (denominator.0.hypot(denominator.1))
+    }
+    /// Returns the magnitude response (dB) of the current coefficients at `frequency` (Hz).
+    pub fn magnitude_db(&self, frequency: f32) -> f32 {
+        20.0 * self.magnitude(frequency).log10()
+    }
+    /// Panics if a parameter is out of its range or NaN.
+    fn check_parameters(&self, frequency: f32, q: f32, gain: f32) {
+        if frequency == 0.0 || !(0.0..self.sample_rate as f32 / 2.0).contains(&frequency) {
+            panic!("!!!Biquad panic: frequency must be in the range (0, sample_rate / 2)");
+        }
+        if q.is_nan() || q <= 0.0 {
+            panic!("!!!Biquad panic: q must be positive");
+        }
+        if !gain.is_finite() {
+            panic!("!!!Biquad panic: gain must be finite");
+        }
+    }
+    fn update_coefficients(&mut self) {
+        let [frequency, q, gain] = self.current;
+        let a = 10.0_f32.powf(gain / 40.0);
+        let w0 = 2.0 * PI * frequency / self.sample_rate as f32;
+        let (sin_w0, cos_w0) = w0.sin_cos();
+        let alpha = sin_w0 / (2.0 * q);
+        let two_sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+        let (b0, b1, b2, a0, a1, a2) = match self.filter_type {
+            BiquadType::LowPass => ((1.0 - cos_w0) / 2.0, 1.0 - cos_w0, (1.0 - cos_w0) / 2.0, 1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha),
+            BiquadType::HighPass => ((1.0 + cos_w0) / 2.0, -(1.0 + cos_w0), (1.0 + cos_w0) / 2.0, 1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha),
+            BiquadType::BandPass => (alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha),
+            BiquadType::Notch => (1.0, -2.0 * cos_w0, 1.0, 1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha),
+            BiquadType::AllPass => (1.0 - alpha, -2.0 * cos_w0, 1.0 + alpha, 1.0 + alpha, -2.0 * cos_w0, 1.0 - alpha),
+            BiquadType::Peaking => (1.0 + alpha * a, -2.0 * cos_w0, 1.0 - alpha * a, 1.0 + alpha / a, -2.0 * cos_w0, 1.0 - alpha / a),
+            BiquadType::LowShelf => (
+                a * ((a + 1.0) - (a - 1.0) * cos_w0 + two_sqrt_a_alpha),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0),
+                a * ((a + 1.0) - (a - 1.0) * cos_w0 - two_sqrt_a_alpha),
+                (a + 1.0) + (a - 1.0) * cos_w0 + two_sqrt_a_alpha,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0),
+                (a + 1.0) + (a - 1.0) * cos_w0 - two_sqrt_a_alpha,
+            ),
+            BiquadType::HighShelf => (
+                a * ((a + 1.0) + (a - 1.0) * cos_w0 + two_sqrt_a_alpha),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+                a * ((a + 1.0) + (a - 1.0) * cos_w0 - two_sqrt_a_alpha),
+                (a + 1.0) - (a - 1.0) * cos_w0 + two_sqrt_a_alpha,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+                (a + 1.0) - (a - 1.0) * cos_w0 - two_sqrt_a_alpha,
+            ),
+        };
+        self.b = [b0 / a0, b1 / a0, b2 / a0];
+        self.a = [a1 / a0, a2 / a0];
+    }
+    /// Moves the current parameters toward the targets, returns `true` if they changed.
+    fn smooth_parameters(&mut self) -> bool {
+        if self.current == self.target {
+            return false;
+        }
+        for (current, target) in self.current.iter_mut().zip(self.target.iter()) {
+            *current = (1.0 - self.smoothing_factor) * target + self.smoothing_factor * *current;
+            if (*current - target).abs() <= 1e-4 * target.abs().max(1.0) {
+                *current = *target; // close enough, stop recomputing the coefficients.
+            }
+        }
+        true
+    }
+}
+
+impl AudioComponent for Biquad {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        if self.smooth_parameters() {
+            self.update_coefficients();
+        }
+        let output = self.b[0] * in_frame + self.state[0];
+        self.state[0] = self.b[1] * in_frame - self.a[0] * output + self.state[1];
+        self.state[1] = self.b[2] * in_frame - self.a[1] * output;
+        output
+    }
+}
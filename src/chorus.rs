@@ -0,0 +1,177 @@
+//! The `Chorus` component mixes the input with several voices (2 to `MAX_VOICES`), each one a copy of the input
+//! delayed by a fractional delay swept by its own LFO: the small pitch and time differences thicken the sound
+//! (chorus with 2-3 voices, ensemble with more voices).
+//!
+//! All the voices read the same circular buffer (`DelayVar::read_at()`) and share one LFO `Phasor`,
+//! voice i being offset by i / voices of a cycle:
+//! delay_i = `BASE_DELAY_MS` + depth / 2 * sin(2pi * (phase + i / voices)).
+//!
+//! The voices are spread across the stereo field, from the left (first voice) to the right (last voice)
+//! with `spread` = 1.0, all centered with `spread` = 0.0 (constant power, see `multi::pan_gains()`).
+//! output = (1 - mix) * input + mix * voices, the voices being scaled by sqrt(2 / voices)
+//! (a centered voice alone has a gain of 1.0).
+//!
+//! # Examples:
+//! Impulse response of 3 voices at 48kHz, depth 4ms (delays in 15ms +/- 2ms), with a very slow LFO:
+//! the voices start at 15ms, 15ms + 1.73ms and 15ms - 1.73ms (LFO phases 0, 1/3 and 2/3).
+//! ```rust
+//! # use mydsp_jack::chorus::Chorus;
+//! # use mydsp_jack::sine_table::SineTable;
+//! # use mydsp_jack::AudioComponent;
+//! use once_cell::sync::Lazy;
+//! static SINETABLE1: Lazy<SineTable> = Lazy::new(|| SineTable::new(4096));
+//! let mut chorus1 = Chorus::new(48000, &SINETABLE1, 3, 0.01, 4.0, 0.0, 1.0);
+//! let response: Vec<f32> = (0..1200).map(|n| chorus1.tick(if n == 0 { 1.0 } else { 0.0 })).collect();
+//! let voices = [720, 720 + 83, 720 - 83]; // frames
+//! let near_voice = |n: usize| voices.iter().any(|v| n.abs_diff(*v) <= 1);
+//! debug_assert!(response.iter().enumerate().all(|(n, x)| *x == 0.0 || near_voice(n)));
+//! for v in voices {
+//!     let voice: f32 = response[v - 1..=v + 1].iter().sum();
+//!     debug_assert!((voice - 1.0 / 3.0_f32.sqrt()).abs() < 1e-3); // sqrt(2 / 3) * sqrt(1 / 2)
+//! }
+//! ```
+//! `spread` widens the stereo image: with 0.0 the channels are equal, with 1.0 they differ:
+//! ```rust
+//! # use mydsp_jack::chorus::Chorus;
+//! # use mydsp_jack::sine_table::SineTable;
+//! use once_cell::sync::Lazy;
+//! static SINETABLE1: Lazy<SineTable> = Lazy::new(|| SineTable::new(4096));
+//! let mut chorus2 = Chorus::new(48000, &SINETABLE1, 4, 0.8, 3.0, 0.0, 0.5);
+//! let input = |n: usize| (2.0 * std::f32::consts::PI * 440.0 * n as f32 / 48000.0).sin();
+//! debug_assert!((0..4800).map(|n| chorus2.tick_stereo(input(n))).all(|[left, right]| left == right));
+//! chorus2.set_spread(1.0);
+//! let difference: f32 = (0..4800).map(|n| chorus2.tick_stereo(input(n))).map(|[left, right]| (left - right).abs()).sum();
+//! debug_assert!(difference > 100.0);
+//! ```
+use crate::AudioComponent;
+use crate::delay_var::DelayVar;
+use crate::multi::{Frame, MultiAudioComponent, pan_gains};
+use crate::phasor::Phasor;
+use crate::sine_table::SineTable;
+
+/// Delay (ms) around which the voices are swept.
+pub const BASE_DELAY_MS: f32 = 15.0;
+/// Maximum depth (ms, peak to peak) of the sweep.
+pub const MAX_DEPTH_MS: f32 = 10.0;
+/// Maximum number of voices.
+pub const MAX_VOICES: usize = 8;
+
+#[derive(Debug)]
+pub struct Chorus {
+    sine_table: &'static SineTable,
+    lfo_phasor: Phasor,
+    delay: DelayVar,
+    sample_rate: usize,
+    voices: usize,
+    voice_gains: [Frame<2>; MAX_VOICES], // pan gains * sqrt(2 / voices).
+    depth: f32, // frames, peak to peak
+    spread: f32,
+    mix: f32,
+}
+
+impl Chorus {
+    /// Returns a `Chorus` instance with `voices` voices in [2, `MAX_VOICES`], an LFO of `rate` (Hz),
+    /// a `depth` in [0.0, `MAX_DEPTH_MS`] (ms, peak to peak), a stereo `spread` in [0.0, 1.0]
+    /// and a dry/wet `mix` in [0.0, 1.0].
+    ///
+    /// # Panics
+    /// The function panics if a parameter is out of its range.
+    /// ```rust, should_panic
+    /// # use mydsp_jack::chorus::Chorus;
+    /// # use mydsp_jack::sine_table::SineTable;
+    /// use once_cell::sync::Lazy;
+    /// static SINETABLE1: Lazy<SineTable> = Lazy::new(|| SineTable::new(4096));
+    /// let chorus_panic = Chorus::new(48000, &SINETABLE1, 1, 0.5, 4.0, 1.0, 0.5);
+    /// ```
+    pub fn new(sample_rate: usize, sine_table: &'static SineTable, voices: usize, rate: f32, depth: f32, spread: f32, mix: f32) -> Self {
+        let max_delay_time = (BASE_DELAY_MS + 0.5 * MAX_DEPTH_MS) * 0.001;
+        let mut chorus = Chorus {
+            sine_table,
+            lfo_phasor: Phasor::with_frequency(sample_rate, rate),
+            delay: DelayVar::with_max_delay_time(sample_rate, max_delay_time),
+            sample_rate,
+            voices: 2,
+            voice_gains: [[0.0; 2]; MAX_VOICES],
+            depth: 0.0,
+            spread: 0.0,
+            mix: 0.0,
+        };
+        chorus.set_voices(voices);
+        chorus.set_depth(depth);
+        chorus.set_spread(spread);
+        chorus.set_mix(mix);
+        chorus
+    }
+    /// Modifies the number of voices in [2, `MAX_VOICES`].
+    pub fn set_voices(&mut self, voices: usize) {
+        if !(2..=MAX_VOICES).contains(&voices) {
+            panic!("!!!Chorus panic: voices must be in the range [2, {}]", MAX_VOICES);
+        }
+        self.voices = voices;
+        self.update_voice_gains();
+    }
+    /// Returns the number of voices.
+    pub fn voices(&self) -> usize {
+        self.voices
+    }
+    /// Modifies the LFO rate (Hz).
+    pub fn set_rate(&mut self, rate: f32) {
+        self.lfo_phasor.set_frequency(rate);
+    }
+    /// Modifies the depth (ms, peak to peak) of the sweep in [0.0, `MAX_DEPTH_MS`].
+    pub fn set_depth(&mut self, depth: f32) {
+        if !(0.0..=MAX_DEPTH_MS).contains(&depth) {
+            panic!("!!!Chorus panic: depth must be in the range [0.0, {}] ms", MAX_DEPTH_MS);
+        }
+        self.depth = depth * 0.001 * self.sample_rate as f32;
+    }
+    /// Modifies the stereo spread in [0.0, 1.0] (0.0 -> all the voices centered, 1.0 -> from left to right).
+    pub fn set_spread(&mut self, spread: f32) {
+        if !(0.0..=1.0).contains(&spread) {
+            panic!("!!!Chorus panic: spread must be in the range [0.0, 1.0]");
+        }
+        self.spread = spread;
+        self.update_voice_gains();
+    }
+    /// Modifies the dry/wet mix in [0.0, 1.0] (0.0 -> input only, 1.0 -> voices only).
+    pub fn set_mix(&mut self, mix: f32) {
+        if !(0.0..=1.0).contains(&mix) {
+            panic!("!!!Chorus panic: mix must be in the range [0.0, 1.0]");
+        }
+        self.mix = mix;
+    }
+    /// Processes one mono frame and returns the stereo output `[left, right]`.
+    pub fn tick_stereo(&mut self, in_frame: f32) -> Frame<2> {
+        self.delay.tick(in_frame);
+        let phase = self.lfo_phasor.tick(in_frame);
+        let base_delay = BASE_DELAY_MS * 0.001 * self.sample_rate as f32;
+        let mut wet = [0.0; 2];
+        for (voice, gains) in self.voice_gains[..self.voices].iter().enumerate() {
+            let lfo = self.sine_table.get_value((phase + voice as f32 / self.voices as f32).fract());
+            let delayed_frame = self.delay.read_at(base_delay + 0.5 * self.depth * lfo);
+            wet[0] += gains[0] * delayed_frame;
+            wet[1] += gains[1] * delayed_frame;
+        }
+        wet.map(|wet| (1.0 - self.mix) * in_frame + self.mix * wet)
+    }
+    fn update_voice_gains(&mut self) {
+        let gain = (2.0 / self.voices as f32).sqrt();
+        for voice in 0..self.voices {
+            let pan = self.spread * (2.0 * voice as f32 / (self.voices - 1) as f32 - 1.0);
+            self.voice_gains[voice] = pan_gains(pan.clamp(-1.0, 1.0)).map(|g| gain * g);
+        }
+    }
+}
+
+impl AudioComponent for Chorus {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        let [left, right] = self.tick_stereo(in_frame);
+        0.5 * (left + right)
+    }
+}
+
+impl MultiAudioComponent<2> for Chorus {
+    fn tick_frame(&mut self, in_frame: Frame<2>) -> Frame<2> {
+        self.tick_stereo(0.5 * (in_frame[0] + in_frame[1]))
+    }
+}
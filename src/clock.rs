@@ -0,0 +1,308 @@
+//! Musical time shared by components: the `Transport` holds the tempo (BPM, quarter notes per minute),
+//! the time signature, the play state and the position, and the `Clock` component advances it
+//! and emits trigger pulses.
+//!
+//! A `Transport` is a handle to a shared state (`Arc` of atomics): cloning it is cheap and every clone sees
+//! the same tempo and position, so a UI thread can change the tempo while the audio thread reads it,
+//! and several components (delays, LFOs, sequencers) can sync to the same time.
+//! Only one `Clock` should advance a given transport (the other components read it), `locate()` can be called
+//! from any thread while it runs.
+//!
+//! The position is available in frames, in quarter notes, or in bar/beat/tick (`Position`, `TICKS_PER_BEAT` per beat).
+//!
+//! # Examples:
+//! A `Clock` emitting a pulse every eighth note at 120 BPM (250 frames at 1kHz), 4/4:
+//! ```rust
+//! # use mydsp_jack::clock::{Clock, Transport, Position};
+//! # use mydsp_jack::tempo::NoteValue;
+//! # use mydsp_jack::AudioComponent;
+//! let transport = Transport::new(1000, 120.0, 4, 4);
+//! let mut clock = Clock::new(transport.clone(), NoteValue::EIGHTH);
+//! transport.play();
+//! let pulses: Vec<usize> = (0..2250).filter(|_| clock.tick(0.0) == 1.0).collect();
+//! debug_assert_eq!(pulses, [0, 250, 500, 750, 1000, 1250, 1500, 1750, 2000]);
+//! // 2250 frames = 4.5 quarter notes = bar 1, beat 0, half of the beat
+//! debug_assert_eq!(transport.position_frames(), 2250);
+//! debug_assert_eq!(transport.position(), Position { bar: 1, beat: 0, tick: 480 });
+//! ```
+//! The tempo is shared: doubling it from another handle doubles the pulse rate, stopping freezes the position:
+//! ```rust
+//! # use mydsp_jack::clock::{Clock, Transport};
+//! # use mydsp_jack::tempo::NoteValue;
+//! # use mydsp_jack::AudioComponent;
+//! let transport = Transport::new(1000, 120.0, 4, 4);
+//! let mut clock = Clock::new(transport.clone(), NoteValue::QUARTER);
+//! let remote = transport.clone(); // e.g. owned by a UI thread
+//! remote.play();
+//! let count = |clock: &mut Clock, nframes: usize| (0..nframes).filter(|_| clock.tick(0.0) == 1.0).count();
+//! debug_assert_eq!(count(&mut clock, 2000), 4); // 500 frames per quarter
+//! remote.set_bpm(240.0);
+//! debug_assert_eq!(count(&mut clock, 2000), 8); // 250 frames per quarter
+//! remote.stop();
+//! debug_assert_eq!(count(&mut clock, 2000), 0);
+//! debug_assert_eq!(transport.position_frames(), 4000);
+//! ```
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use crate::AudioComponent;
+use crate::phasor::Phasor;
+use crate::tempo::NoteValue;
+
+/// Resolution of `Position::tick` (ticks per beat).
+pub const TICKS_PER_BEAT: u32 = 960;
+
+/// Musical position, all fields start at 0 (the first beat of the first bar is `{ bar: 0, beat: 0, tick: 0 }`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub bar: u32,
+    /// Beat in the bar, in units of the time signature (e.g. eighth notes in 6/8).
+    pub beat: u32,
+    /// Tick in the beat, in [0, `TICKS_PER_BEAT`).
+    pub tick: u32,
+}
+
+#[derive(Debug)]
+struct TransportState {
+    bpm: AtomicU32, // f32 bits
+    beats_per_bar: AtomicU32,
+    beat_unit: AtomicU32,
+    playing: AtomicBool,
+    frames: AtomicU64,
+    quarters: AtomicU64, // f64 bits, position in quarter notes.
+}
+
+/// Shared tempo, time signature and position, see the module documentation.
+#[derive(Debug, Clone)]
+pub struct Transport {
+    state: Arc<TransportState>,
+    sample_rate: usize,
+}
+
+impl Transport {
+    /// Returns a stopped `Transport` at position 0, with a tempo in `bpm` and a time signature
+    /// `beats_per_bar` / `beat_unit` (e.g. 6 / 8).
+    ///
+    /// # Panics
+    /// The function panics if `bpm` is not positive or not finite, if `beats_per_bar` is 0 or if `beat_unit` is not a power of 2.
+    /// ```rust, should_panic
+    /// # use mydsp_jack::clock::Transport;
+    /// let transport_panic = Transport::new(48000, 120.0, 4, 3);
+    /// ```
+    pub fn new(sample_rate: usize, bpm: f32, beats_per_bar: u32, beat_unit: u32) -> Self {
+        let transport = Transport {
+            state: Arc::new(TransportState {
+                bpm: AtomicU32::new(0),
+                beats_per_bar: AtomicU32::new(0),
+                beat_unit: AtomicU32::new(0),
+                playing: AtomicBool::new(false),
+                frames: AtomicU64::new(0),
+                quarters: AtomicU64::new(0.0_f64.to_bits()),
+            }),
+            sample_rate,
+        };
+        transport.set_bpm(bpm);
+        transport.set_time_signature(beats_per_bar, beat_unit);
+        transport
+    }
+    /// Modifies the tempo (quarter notes per minute).
+    ///
+    /// # Panics
+    /// The function panics if `bpm` is not positive or not finite (NaN included).
+    /// ```rust, should_panic
+    /// # use mydsp_jack::clock::Transport;
+    /// let transport = Transport::new(48000, 120.0, 4, 4);
+    /// transport.set_bpm(f32::NAN);
+    /// ```
+    pub fn set_bpm(&self, bpm: f32) {
+        if !bpm.is_finite() || bpm <= 0.0 {
+            panic!("!!!Transport panic: bpm must be positive and finite");
+        }
+        self.state.bpm.store(bpm.to_bits(), Ordering::Relaxed);
+    }
+    /// Returns the tempo (quarter notes per minute).
+    pub fn bpm(&self) -> f32 {
+        f32::from_bits(self.state.bpm.load(Ordering::Relaxed))
+    }
+    /// Modifies the time signature `beats_per_bar` / `beat_unit`.
+    pub fn set_time_signature(&self, beats_per_bar: u32, beat_unit: u32) {
+        if beats_per_bar == 0 || !beat_unit.is_power_of_two() {
+            panic!("!!!Transport panic: beats_per_bar must be positive and beat_unit a power of 2");
+        }
+        self.state.beats_per_bar.store(beats_per_bar, Ordering::Relaxed);
+        self.state.beat_unit.store(beat_unit, Ordering::Relaxed);
+    }
+    /// Returns the time signature `(beats_per_bar, beat_unit)`.
+    pub fn time_signature(&self) -> (u32, u32) {
+        (self.state.beats_per_bar.load(Ordering::Relaxed), self.state.beat_unit.load(Ordering::Relaxed))
+    }
+    /// Starts (or resumes) the transport.
+    pub fn play(&self) {
+        self.state.playing.store(true, Ordering::Relaxed);
+    }
+    /// Stops the transport, the position is kept.
+    pub fn stop(&self) {
+        self.state.playing.store(false, Ordering::Relaxed);
+    }
+    /// Returns `true` if the transport is playing.
+    pub fn is_playing(&self) -> bool {
+        self.state.playing.load(Ordering::Relaxed)
+    }
+    /// Moves the position to a number of quarter notes from the start, the number of frames played is not changed.
+    ///
+    /// # Examples:
+    /// Locating from another thread while the clock runs (the clock resumes from the new position):
+    /// ```rust
+    /// # use mydsp_jack::clock::{Clock, Transport};
+    /// # use mydsp_jack::tempo::NoteValue;
+    /// # use mydsp_jack::AudioComponent;
+    /// let transport = Transport::new(1000, 120.0, 4, 4);
+    /// let mut clock = Clock::new(transport.clone(), NoteValue::QUARTER);
+    /// let remote = transport.clone();
+    /// transport.play();
+    /// std::thread::scope(|scope| {
+    ///     scope.spawn(|| for _ in 0..100000 { clock.tick(0.0); });
+    ///     remote.locate(1000.0);
+    /// });
+    /// debug_assert!(transport.position_quarters() >= 1000.0);
+    /// debug_assert_eq!(transport.position_frames(), 100000);
+    /// // after a tempo change, the frames still count the frames played
+    /// transport.set_bpm(60.0);
+    /// transport.locate(2.0);
+    /// debug_assert_eq!(clock.tick(0.0), 1.0); // pulse on the located quarter
+    /// debug_assert_eq!(transport.position_frames(), 100001);
+    /// debug_assert_eq!(transport.position_quarters(), 2.001);
+    /// ```
+    pub fn locate(&self, quarters: f64) {
+        self.state.quarters.store(quarters.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+    /// Returns the number of frames played since the start (not changed by `locate()`).
+    ///
+    /// The frames and the quarters are counted separately: read from another thread while playing,
+    /// they can be one frame apart.
+    pub fn position_frames(&self) -> u64 {
+        self.state.frames.load(Ordering::Relaxed)
+    }
+    /// Returns the position in quarter notes since the start (follows the tempo changes).
+    pub fn position_quarters(&self) -> f64 {
+        f64::from_bits(self.state.quarters.load(Ordering::Relaxed))
+    }
+    /// Returns the position in bar/beat/tick.
+    pub fn position(&self) -> Position {
+        let (beats_per_bar, beat_unit) = self.time_signature();
+        let beats = self.position_quarters() * beat_unit as f64 / 4.0 + 1e-9; // in beat units.
+        let whole_beats = beats.floor();
+        Position {
+            bar: (whole_beats / beats_per_bar as f64) as u32,
+            beat: (whole_beats as u64 % beats_per_bar as u64) as u32,
+            tick: ((beats - whole_beats) * TICKS_PER_BEAT as f64) as u32,
+        }
+    }
+    /// Returns the sample rate of the transport.
+    pub fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+    /// Returns the duration (seconds) of a note value at the current tempo, e.g. for a delay time.
+    pub fn note_seconds(&self, note: NoteValue) -> f32 {
+        note.seconds(self.bpm())
+    }
+    /// Returns the duration (frames) of a note value at the current tempo.
+    pub fn note_frames(&self, note: NoteValue) -> usize {
+        note.frames(self.bpm(), self.sample_rate)
+    }
+    /// Returns the frequency (Hz) of one cycle per note value at the current tempo, e.g. for an LFO rate.
+    pub fn note_frequency(&self, note: NoteValue) -> f32 {
+        note.frequency(self.bpm())
+    }
+    /// Sets the frequency of `phasor` to one cycle per note value at the current tempo.
+    ///
+    /// # Examples:
+    /// ```rust
+    /// # use mydsp_jack::clock::Transport;
+    /// # use mydsp_jack::phasor::Phasor;
+    /// # use mydsp_jack::tempo::NoteValue;
+    /// let transport = Transport::new(48000, 120.0, 4, 4);
+    /// let mut lfo = Phasor::with_frequency(48000, 1.0);
+    /// transport.sync_phasor(&mut lfo, NoteValue::HALF); // one cycle per half note: 1Hz at 120 BPM
+    /// debug_assert_eq!(lfo.frequency(), 1.0);
+    /// transport.sync_phasor(&mut lfo, NoteValue::EIGHTH.triplet()); // 6Hz
+    /// debug_assert!((lfo.frequency() - 6.0).abs() < 1e-4);
+    /// ```
+    /// # Panics
+    /// The function panics if the `Phasor` was not created with `Phasor::with_frequency()`.
+    pub fn sync_phasor(&self, phasor: &mut Phasor, note: NoteValue) {
+        phasor.set_frequency(self.note_frequency(note));
+    }
+}
+
+/// Advances a `Transport` and returns 1.0 on the first frame of each `subdivision` (0.0 otherwise).
+#[derive(Debug)]
+pub struct Clock {
+    transport: Transport,
+    subdivision: f64, // quarter notes
+    last_pulse: Option<u64>, // index of the last subdivision pulsed.
+    last_quarters: Option<f64>, // position of the last frame ticked while playing.
+}
+
+impl Clock {
+    /// Returns a `Clock` advancing `transport` (when playing) and pulsing every `subdivision`.
+    pub fn new(transport: Transport, subdivision: NoteValue) -> Self {
+        Clock {
+            transport,
+            subdivision: subdivision.beats() as f64,
+            last_pulse: None,
+            last_quarters: None,
+        }
+    }
+    /// Modifies the subdivision of the pulses, the next pulse is on the next subdivision of the new grid.
+    ///
+    /// # Examples:
+    /// Switching from eighth notes to quarter notes in the middle of the first quarter (120 BPM at 1kHz):
+    /// ```rust
+    /// # use mydsp_jack::clock::{Clock, Transport};
+    /// # use mydsp_jack::tempo::NoteValue;
+    /// # use mydsp_jack::AudioComponent;
+    /// let transport = Transport::new(1000, 120.0, 4, 4);
+    /// let mut clock = Clock::new(transport.clone(), NoteValue::EIGHTH);
+    /// transport.play();
+    /// let mut pulses: Vec<usize> = (0..400).filter(|_| clock.tick(0.0) == 1.0).collect();
+    /// clock.set_subdivision(NoteValue::QUARTER);
+    /// pulses.extend((400..1200).filter(|_| clock.tick(0.0) == 1.0));
+    /// debug_assert_eq!(pulses, [0, 250, 500, 1000]); // no extra pulse at 400
+    /// ```
+    pub fn set_subdivision(&mut self, subdivision: NoteValue) {
+        self.subdivision = subdivision.beats() as f64;
+        // index of the last frame ticked in the new grid.
+        self.last_pulse = self.last_quarters.map(|quarters| self.pulse_index(quarters));
+    }
+    /// Returns a handle to the transport.
+    pub fn transport(&self) -> &Transport {
+        &self.transport
+    }
+    /// Returns the index of the subdivision containing the position `quarters`.
+    fn pulse_index(&self, quarters: f64) -> u64 {
+        (quarters / self.subdivision + 1e-9).floor() as u64
+    }
+}
+
+impl AudioComponent for Clock {
+    fn tick(&mut self, _in_frame: f32) -> f32 {
+        if !self.transport.is_playing() {
+            return 0.0;
+        }
+        let state = &self.transport.state;
+        let increment = self.transport.bpm() as f64 / (60.0 * self.transport.sample_rate as f64);
+        // a `locate()` between the load and the store fails the exchange, the frame is then at the located position.
+        let mut bits = state.quarters.load(Ordering::Relaxed);
+        while let Err(located) = state.quarters.compare_exchange_weak(
+            bits, (f64::from_bits(bits) + increment).to_bits(), Ordering::Relaxed, Ordering::Relaxed) {
+            bits = located;
+        }
+        state.frames.fetch_add(1, Ordering::Relaxed);
+        let quarters = f64::from_bits(bits);
+        let index = self.pulse_index(quarters);
+        let pulse = if self.last_pulse != Some(index) { 1.0 } else { 0.0 };
+        self.last_pulse = Some(index);
+        self.last_quarters = Some(quarters);
+        pulse
+    }
+}
@@ -0,0 +1,118 @@
+//! The comb filters `FeedforwardComb` and `FeedbackComb`, built on a fixed `Delay`:
+//! building blocks of reverbs (`FeedbackComb` with damping is the comb of Freeverb) and physical models.
+//!
+//! * `FeedforwardComb`: output = input + gain * input delayed by nframes_delay,
+//!   notches at the odd multiples of sample_rate / (2 * nframes_delay) for a positive gain.
+//! * `FeedbackComb`: output = input delayed by nframes_delay + feedback * lowpass(output delayed by nframes_delay),
+//!   a decaying train of echoes, the one-pole low-pass (`damping`) makes the high frequencies decay faster.
+//!
+//! # Examples:
+//! Impulse responses:
+//! ```rust
+//! # use mydsp_jack::comb::{FeedforwardComb, FeedbackComb};
+//! # use mydsp_jack::AudioComponent;
+//! let mut feedforward1 = FeedforwardComb::new(3, 0.5);
+//! let response: Vec<f32> = (0..8).map(|n| feedforward1.tick(if n == 0 { 1.0 } else { 0.0 })).collect();
+//! debug_assert_eq!(response, [1.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0]);
+//! let mut feedback1 = FeedbackComb::new(3, 0.5, None); // no damping
+//! let response: Vec<f32> = (0..10).map(|n| feedback1.tick(if n == 0 { 1.0 } else { 0.0 })).collect();
+//! debug_assert_eq!(response, [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.25]);
+//! let mut feedback2 = FeedbackComb::new(3, 0.5, 0.5); // the echoes are low-passed
+//! let response: Vec<f32> = (0..8).map(|n| feedback2.tick(if n == 0 { 1.0 } else { 0.0 })).collect();
+//! debug_assert_eq!(response, [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.25, 0.125]);
+//! ```
+use crate::AudioComponent;
+use crate::delay::Delay;
+
+#[derive(Debug)]
+pub struct FeedforwardComb {
+    delay: Delay,
+    gain: f32, // gain of the delayed input.
+}
+
+impl FeedforwardComb {
+    /// Returns a `FeedforwardComb` instance with a delay of `nframes_delay` frames and a `gain` of the delayed input.
+    ///
+    /// # Panics
+    /// The function panics if `nframes_delay` is not a positive integer (see `Delay`).
+    pub fn new(nframes_delay: usize, gain: f32) -> Self {
+        FeedforwardComb {
+            delay: Delay::new(nframes_delay),
+            gain,
+        }
+    }
+    /// Modifies the gain of the delayed input.
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain;
+    }
+    /// Clears the delay line.
+    pub fn reset(&mut self) {
+        self.delay.reset();
+    }
+}
+
+impl AudioComponent for FeedforwardComb {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        in_frame + self.gain * self.delay.tick(in_frame)
+    }
+}
+
+#[derive(Debug)]
+pub struct FeedbackComb {
+    delay: Delay,
+    feedback: f32,
+    damping: f32, // coefficient of the one-pole low-pass in the loop, 0.0 -> no low-pass.
+    lowpass_state: f32,
+}
+
+impl FeedbackComb {
+    /// Returns a `FeedbackComb` instance with a delay of `nframes_delay` frames, a `feedback` in (-1.0, 1.0)
+    /// and a `damping` in [0.0, 1.0) (default 0.0, no damping).
+    ///
+    /// # Panics
+    /// The function panics if `nframes_delay` is not a positive integer, if `feedback` is not in the range (-1.0, 1.0)
+    /// or if `damping` is not in the range [0.0, 1.0).
+    /// ```rust, should_panic
+    /// # use mydsp_jack::comb::FeedbackComb;
+    /// let feedback_panic = FeedbackComb::new(100, 1.0, None);
+    /// ```
+    pub fn new(nframes_delay: usize, feedback: f32, damping: impl Into<Option<f32>>) -> Self {
+        let mut comb = FeedbackComb {
+            delay: Delay::new(nframes_delay),
+            feedback: 0.0,
+            damping: 0.0,
+            lowpass_state: 0.0,
+        };
+        comb.set_feedback(feedback);
+        comb.set_damping(damping.into().unwrap_or(0.0));
+        comb
+    }
+    /// Modifies the feedback in (-1.0, 1.0).
+    pub fn set_feedback(&mut self, feedback: f32) {
+        if feedback <= -1.0 || feedback >= 1.0 {
+            panic!("!!!FeedbackComb panic: feedback must be in the range (-1.0, 1.0)");
+        }
+        self.feedback = feedback;
+    }
+    /// Modifies the damping in [0.0, 1.0) (0.0 -> no damping, higher -> darker echoes).
+    pub fn set_damping(&mut self, damping: f32) {
+        if !(0.0..1.0).contains(&damping) {
+            panic!("!!!FeedbackComb panic: damping must be in the range [0.0, 1.0)");
+        }
+        self.damping = damping;
+    }
+    /// Clears the delay line and the low-pass.
+    pub fn reset(&mut self) {
+        self.delay.reset();
+        self.lowpass_state = 0.0;
+    }
+}
+
+impl AudioComponent for FeedbackComb {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        let delayed_frame = self.delay.read();
+        self.lowpass_state = (1.0 - self.damping) * delayed_frame + self.damping * self.lowpass_state;
+        self.delay.tick(in_frame + self.feedback * self.lowpass_state);
+        delayed_frame
+    }
+}
@@ -33,7 +33,7 @@ impl Delay {
     /// let delay2 = Delay::new(0);
     /// ```
     pub fn new(nframes_delay: usize, ) -> Self {
-        if nframes_delay <= 0 {
+        if nframes_delay == 0 {
             panic!("!!!Panic: nframes_delay (circular buffer size) must be a positive integer");
         }
         Delay {
@@ -61,6 +61,20 @@ impl Delay {
     pub fn read(&self) -> f32 {
         self.buffer_delay[self.index]
     }
+    /// Clears the circular buffer (silence), the size is kept.
+    pub fn reset(&mut self) {
+        self.buffer_delay.fill(0.0);
+        self.index = 0;
+    }
+    /// Returns the contiguous part of the circular buffer starting at the current index 
+    /// (at most `max_len` frames, stops at the end of the buffer) and moves the index after it.  
+    /// memo: before being overwritten, each frame of the segment is the delayed frame of the same position.
+    pub(crate) fn next_segment(&mut self, max_len: usize) -> &mut [f32] {
+        let start = self.index;
+        let end = (start + max_len).min(self.nframes_delay);
+        self.index = end % self.nframes_delay;
+        &mut self.buffer_delay[start..end]
+    }
 }
 
 impl AudioComponent for Delay {
@@ -70,4 +84,15 @@ impl AudioComponent for Delay {
         self.index = (self.index + 1) % self.nframes_delay; // update write position.
         delayed_frame
     } 
+    fn process_block(&mut self, input: &[f32], output: &mut [f32]) {
+        crate::check_block(input, output);
+        let mut start = 0;
+        while start < input.len() {
+            let segment = self.next_segment(input.len() - start);
+            let end = start + segment.len();
+            output[start..end].copy_from_slice(segment); // read.
+            segment.copy_from_slice(&input[start..end]); // write.
+            start = end;
+        }
+    }
 }
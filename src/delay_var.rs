@@ -7,15 +7,69 @@
 //! time of delay = offset_delay / sample_rate  
 //! max time delay = buffer_size - 1 / sample_rate  
 //! offset_delay < buffer_size  
+//! 
+//! The delay can also be fractional (`set_delay()`), read with linear interpolation between two frames, 
+//! e.g. offset_delay = 1.5 -> (frame at t-1 + frame at t-2) / 2.
+//! 
+//! The interpolation of fractional delays is selected with `set_interpolation()` (`DelayInterpolation`):
+//! * `Linear` (default): cheap, but a low-pass whose cutoff moves with the fraction (dull and modulated highs).
+//! * `Lagrange`: 3rd order polynomial through 4 frames, much flatter up to high frequencies.
+//! * `Thiran`: 1st order allpass, flat magnitude (no loss in feedback loops, e.g. reverbs),
+//!   best for slowly modulated delays (its state makes fast jumps ring a little).
+//! 
+//! With `with_max_delay_time()`, the delay can be set in seconds (`set_delay_time()`),
+//! and `set_ramp()`/`set_ramp_time()` make the next delays glide linearly instead of jumping (no zipper noise).
+//! 
+//! # Examples:
+//! Accuracy of a delay of 10.3 frames on a 6kHz sinusoid at 48kHz:
+//! ```rust
+//! # use mydsp_jack::delay_var::{DelayVar, DelayInterpolation};
+//! # use mydsp_jack::AudioComponent;
+//! let sinusoid = |n: f32| (2.0 * std::f32::consts::PI * 6000.0 * n / 48000.0).sin();
+//! let mut errors = vec![];
+//! for interpolation in [DelayInterpolation::Linear, DelayInterpolation::Lagrange, DelayInterpolation::Thiran] {
+//!     let mut delay1 = DelayVar::new(64, 0);
+//!     delay1.set_interpolation(interpolation);
+//!     delay1.set_delay(10.3);
+//!     let mut max_error = 0.0_f32;
+//!     for n in 0..4800 {
+//!         let out_frame = delay1.tick(sinusoid(n as f32));
+//!         if n > 100 { max_error = max_error.max((out_frame - sinusoid(n as f32 - 10.3)).abs()); }
+//!     }
+//!     errors.push(max_error);
+//! }
+//! debug_assert!(errors[0] < 0.1); // linear
+//! debug_assert!(errors[1] < 0.01); // Lagrange
+//! debug_assert!(errors[2] < 0.05); // Thiran (phase error only)
+//! ```
 use crate::AudioComponent;
 
+/// Interpolation of the fractional delays of `DelayVar`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DelayInterpolation {
+    /// Linear interpolation between 2 frames.
+    #[default]
+    Linear,
+    /// 3rd order Lagrange interpolation between 4 frames (linear if the buffer is smaller than 4 frames).
+    Lagrange,
+    /// 1st order Thiran allpass (linear for delays shorter than 0.5 frame).
+    Thiran,
+}
+
 #[derive(Debug)]
 pub struct DelayVar {
     buffer_delay: Vec<f32>, // circular buffer to store delayed frames.
     index_write: usize, // position in buffer_delay to write.
     index_read: usize, // position in buffer_delay to read.
     buffer_size: usize, // size of the circular buffer (fixed).
-    offset_delay: usize, // offset of the read index from the write index.
+    offset_delay: f32, // offset of the read index from the write index (fractional).
+    interpolation: DelayInterpolation,
+    allpass_state: f32, // previous output of the Thiran allpass.
+    target_delay: f32, // delay reached at the end of the ramp.
+    ramp_step: f32, // change of offset_delay per frame during a ramp.
+    ramp_frames_left: usize,
+    ramp_frames: usize, // length of the ramps of the next delays, 0 -> immediate.
+    sample_rate: Option<usize>, // known if created with `with_max_delay_time()`.
 }
 
 impl DelayVar {
@@ -44,7 +98,7 @@ impl DelayVar {
     /// let delay_panic = DelayVar::new(0, 1);
     /// ```
     pub fn new(buffer_size: usize, offset_delay: usize) -> Self {
-        if buffer_size <= 0 {
+        if buffer_size == 0 {
             panic!("!!!Panic: buffer_size must be a positive integer");
         }
         if offset_delay >= buffer_size {
@@ -55,9 +109,40 @@ impl DelayVar {
             index_write: 0,
             index_read: 0,
             buffer_size,
-            offset_delay,
+            offset_delay: offset_delay as f32,
+            interpolation: DelayInterpolation::default(),
+            allpass_state: 0.0,
+            target_delay: offset_delay as f32,
+            ramp_step: 0.0,
+            ramp_frames_left: 0,
+            ramp_frames: 0,
+            sample_rate: None,
         }
     }
+    /// Returns a `DelayVar` instance able to delay up to `max_delay_time` seconds (no delay at first),
+    /// whose delay can be set in seconds.
+    /// 
+    /// # Examples:
+    /// ```rust
+    /// # use mydsp_jack::delay_var::DelayVar;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut delay5 = DelayVar::with_max_delay_time(1000, 0.5);
+    /// delay5.set_delay_time(0.0025); // 2.5 frames at 1000Hz
+    /// debug_assert_eq!(delay5.delay(), 2.5);
+    /// debug_assert_eq!(delay5.delay_time(), 0.0025);
+    /// ```
+    /// # Panics
+    /// The function panics if `sample_rate` is 0 or if `max_delay_time` is negative.
+    pub fn with_max_delay_time(sample_rate: usize, max_delay_time: f32) -> Self {
+        if sample_rate == 0 || max_delay_time < 0.0 {
+            panic!("!!!Panic: sample_rate must be a positive integer and max_delay_time must be non-negative");
+        }
+        // +3 frames: the frame just written (offset 0) and the 2 frames read beyond the delay by the Lagrange interpolation.
+        let buffer_size = (max_delay_time * sample_rate as f32).ceil() as usize + 3;
+        let mut delay_var = DelayVar::new(buffer_size, 0);
+        delay_var.sample_rate = Some(sample_rate);
+        delay_var
+    }
     pub fn read(&self) -> f32 {
         self.buffer_delay[self.index_read]
     }
@@ -86,15 +171,218 @@ impl DelayVar {
         if offset_delay >= self.buffer_size {
             panic!("!!!Panic: offset_delay must be less than buffer_size");
         }
-        self.offset_delay = offset_delay;
+        self.set_delay(offset_delay as f32);
+    }
+    /// Sets a fractional `offset_delay` (in frames) to modify time of delay, read with linear interpolation.
+    /// 
+    /// # Examples:
+    /// Seting a `offset_delay` of 1.5 frames:
+    /// ```rust
+    /// # use mydsp_jack::delay_var::DelayVar;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut delay4 = DelayVar::new(4, 0);
+    /// delay4.set_delay(1.5);
+    /// debug_assert_eq!(delay4.tick(2.0), 0.0);
+    /// debug_assert_eq!(delay4.tick(4.0), 1.0); // (2.0 + 0.0) / 2
+    /// debug_assert_eq!(delay4.tick(6.0), 3.0); // (4.0 + 2.0) / 2
+    /// ```
+    /// # Panics
+    /// The function panics if `offset_delay` is not in the range [0, buffer_size - 1].
+    /// ```rust, should_panic
+    /// # use mydsp_jack::delay_var::DelayVar;
+    /// let mut delay_panic = DelayVar::new(3, 1);
+    /// delay_panic.set_delay(2.5);
+    /// ```
+    pub fn set_delay(&mut self, offset_delay: f32) {
+        if !(0.0..=(self.buffer_size - 1) as f32).contains(&offset_delay) {
+            panic!("!!!Panic: offset_delay must be in the range [0, buffer_size - 1]");
+        }
+        self.target_delay = offset_delay;
+        if self.ramp_frames == 0 {
+            self.offset_delay = offset_delay;
+            self.ramp_frames_left = 0;
+        } else {
+            self.ramp_step = (offset_delay - self.offset_delay) / self.ramp_frames as f32;
+            self.ramp_frames_left = self.ramp_frames;
+        }
+    }
+    /// Returns the current `offset_delay` (in frames), which moves toward the last delay set during a ramp.
+    pub fn delay(&self) -> f32 {
+        self.offset_delay
+    }
+    /// Returns the `offset_delay` (in frames) of the next `tick()`, one step further than `delay()` during a ramp.
+    pub fn next_delay(&self) -> f32 {
+        match self.ramp_frames_left {
+            0 => self.offset_delay,
+            1 => self.target_delay,
+            _ => self.offset_delay + self.ramp_step,
+        }
+    }
+    /// Sets the delay in seconds.
+    /// 
+    /// # Panics
+    /// The function panics if the `DelayVar` was not created with `with_max_delay_time()` (unknown sample rate),
+    /// or if the delay is out of the buffer.
+    pub fn set_delay_time(&mut self, delay_time: f32) {
+        self.set_delay(delay_time * self.sample_rate() as f32);
+    }
+    /// Returns the current delay in seconds.
+    /// 
+    /// # Panics
+    /// The function panics if the `DelayVar` was not created with `with_max_delay_time()` (unknown sample rate).
+    pub fn delay_time(&self) -> f32 {
+        self.offset_delay / self.sample_rate() as f32
+    }
+    /// Modifies the interpolation of the fractional delays.
+    ///
+    /// The state of the Thiran allpass restarts from the previous frame read at the current delay
+    /// (a cleared state would make a step, e.g. 0.13 on a constant input for a delay of 10.3 frames).
+    ///
+    /// # Examples:
+    /// Switching to `Thiran` while a constant input is delayed:
+    /// ```rust
+    /// # use mydsp_jack::delay_var::{DelayVar, DelayInterpolation};
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut delay9 = DelayVar::new(64, 0);
+    /// delay9.set_delay(10.3);
+    /// (0..100).for_each(|_| { delay9.tick(1.0); });
+    /// delay9.set_interpolation(DelayInterpolation::Thiran);
+    /// debug_assert!((0..100).all(|_| (delay9.tick(1.0) - 1.0).abs() < 1e-6));
+    /// ```
+    pub fn set_interpolation(&mut self, interpolation: DelayInterpolation) {
+        self.interpolation = interpolation;
+        self.allpass_state = self.read_at(self.offset_delay);
+    }
+    /// Returns the interpolation of the fractional delays.
+    pub fn interpolation(&self) -> DelayInterpolation {
+        self.interpolation
+    }
+    /// Makes the next delays set by `set_delay()` (or `set_delay_time()`) glide linearly during `nframes`,
+    /// 0 -> immediate (default).
+    /// 
+    /// # Examples:
+    /// Gliding from 0 to 4 frames during 4 frames:
+    /// ```rust
+    /// # use mydsp_jack::delay_var::DelayVar;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut delay6 = DelayVar::new(8, 0);
+    /// delay6.set_ramp(4);
+    /// delay6.set_delay(4.0);
+    /// for expected in [1.0, 2.0, 3.0, 4.0, 4.0] {
+    ///     delay6.tick(0.0);
+    ///     debug_assert_eq!(delay6.delay(), expected);
+    /// }
+    /// ```
+    /// Sweeping a delay of a 1kHz sinusoid from 1ms to 10ms: with a ramp of 0.1s the pitch glides,
+    /// without ramp the jump makes a click (discontinuity):
+    /// ```rust
+    /// # use mydsp_jack::delay_var::DelayVar;
+    /// # use mydsp_jack::AudioComponent;
+    /// fn max_step(ramp_time: f32) -> f32 {
+    ///     let mut delay7 = DelayVar::with_max_delay_time(48000, 0.02);
+    ///     delay7.set_delay_time(0.001);
+    ///     delay7.set_ramp_time(ramp_time);
+    ///     let mut previous_frame = 0.0;
+    ///     let mut max_step = 0.0_f32;
+    ///     for n in 0..9600 {
+    ///         if n == 4800 { delay7.set_delay_time(0.0103); }
+    ///         let out_frame = delay7.tick((2.0 * std::f32::consts::PI * 1000.0 * n as f32 / 48000.0).sin());
+    ///         if n > 1000 { max_step = max_step.max((out_frame - previous_frame).abs()); }
+    ///         previous_frame = out_frame;
+    ///     }
+    ///     max_step
+    /// }
+    /// debug_assert!(max_step(0.1) < 0.15); // the sinusoid moves by up to 0.13 per frame
+    /// debug_assert!(max_step(0.0) > 0.5);
+    /// ```
+    pub fn set_ramp(&mut self, nframes: usize) {
+        self.ramp_frames = nframes;
+    }
+    /// Same as `set_ramp()` with a duration in seconds.
+    /// 
+    /// # Panics
+    /// The function panics if the `DelayVar` was not created with `with_max_delay_time()` (unknown sample rate).
+    pub fn set_ramp_time(&mut self, ramp_time: f32) {
+        self.set_ramp((ramp_time.max(0.0) * self.sample_rate() as f32).round() as usize);
+    }
+    /// Returns the frame delayed by `delay` (fractional, in frames, linear interpolation)
+    /// from the last frame written by `tick()`, e.g. to read several taps from one buffer.
+    /// 
+    /// # Examples:
+    /// ```rust
+    /// # use mydsp_jack::delay_var::DelayVar;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut delay8 = DelayVar::new(4, 0);
+    /// for in_frame in [1.0, 2.0, 3.0] { delay8.tick(in_frame); }
+    /// debug_assert_eq!(delay8.read_at(0.0), 3.0);
+    /// debug_assert_eq!(delay8.read_at(2.0), 1.0);
+    /// debug_assert_eq!(delay8.read_at(0.5), 2.5);
+    /// ```
+    /// # Panics
+    /// The function panics if `delay` is not in the range [0, buffer_size - 1].
+    pub fn read_at(&self, delay: f32) -> f32 {
+        if !(0.0..=(self.buffer_size - 1) as f32).contains(&delay) {
+            panic!("!!!Panic: delay must be in the range [0, buffer_size - 1]");
+        }
+        let offset = delay as usize;
+        let frac = delay - offset as f32;
+        // `tick()` moved index_write after the last written frame.
+        let frame = self.frame_at(offset + 1);
+        if frac > 0.0 {
+            frame + frac * (self.frame_at(offset + 2) - frame)
+        } else {
+            frame
+        }
+    }
+    /// Returns the frame delayed by `offset` (integer) frames from the last written frame.
+    fn frame_at(&self, offset: usize) -> f32 {
+        self.buffer_delay[(self.index_write + 2 * self.buffer_size - offset) % self.buffer_size]
+    }
+    fn sample_rate(&self) -> usize {
+        self.sample_rate.unwrap_or_else(|| panic!("!!!Panic: unknown sample rate, create the DelayVar with DelayVar::with_max_delay_time()"))
     }
 }
 
 impl AudioComponent for DelayVar {
     fn tick(&mut self, in_frame: f32) -> f32 {
+        if self.ramp_frames_left > 0 {
+            self.offset_delay = self.next_delay();
+            self.ramp_frames_left -= 1;
+        }
         self.buffer_delay[self.index_write] = in_frame; // write
-        self.index_read = (self.index_write + self.buffer_size - self.offset_delay) % self.buffer_size; // update read position
-        let delayed_frame = self.buffer_delay[self.index_read]; // read
+        let offset = self.offset_delay as usize;
+        let frac = self.offset_delay - offset as f32;
+        self.index_read = (self.index_write + self.buffer_size - offset) % self.buffer_size; // update read position
+        let mut delayed_frame = self.buffer_delay[self.index_read]; // read
+        match self.interpolation {
+            DelayInterpolation::Lagrange if self.buffer_size >= 4 => {
+                // 4 frames around the delay: offsets base..base + 3, d in [0, 3].
+                let base = (offset.max(1) - 1).min(self.buffer_size - 4);
+                let d = self.offset_delay - base as f32;
+                let x = [self.frame_at(base), self.frame_at(base + 1), self.frame_at(base + 2), self.frame_at(base + 3)];
+                delayed_frame = -(d - 1.0) * (d - 2.0) * (d - 3.0) / 6.0 * x[0]
+                    + d * (d - 2.0) * (d - 3.0) / 2.0 * x[1]
+                    - d * (d - 1.0) * (d - 3.0) / 2.0 * x[2]
+                    + d * (d - 1.0) * (d - 2.0) / 6.0 * x[3];
+            }
+            DelayInterpolation::Thiran if self.offset_delay >= 0.5 => {
+                // fractional part in [0.5, 1.5) for a well-behaved allpass.
+                let base = (self.offset_delay - 0.5).floor() as usize;
+                let d = self.offset_delay - base as f32;
+                let coef = (1.0 - d) / (1.0 + d);
+                let older_frame = self.frame_at((base + 1).min(self.buffer_size - 1));
+                delayed_frame = coef * self.frame_at(base) + older_frame - coef * self.allpass_state;
+            }
+            _ => {
+                if frac > 0.0 {
+                    let older_frame = self.buffer_delay[(self.index_read + self.buffer_size - 1) % self.buffer_size];
+                    delayed_frame += frac * (older_frame - delayed_frame); // linear interpolation
+                }
+            }
+        }
+        if self.interpolation == DelayInterpolation::Thiran {
+            self.allpass_state = delayed_frame;
+        }
         self.index_write = (self.index_write + 1) % self.buffer_size; // update write position
         delayed_frame
     }
@@ -14,16 +14,24 @@ impl Distortion {
             gain,
         }
     }
-    fn cubic(&self, x: f32) -> f32 {
-        x - x.powi(3) / 3.0
-    }
+}
+
+/// Cubic soft clipper: clamps `x` to [-1.0, 1.0] then returns `x - x^3 / 3` in [-2/3, 2/3],
+/// with a unity slope around 0 (also used per stage by `LadderFilter`).
+pub(crate) fn soft_clip(x: f32) -> f32 {
+    let x = x.clamp(-1.0, 1.0);
+    x - x.powi(3) / 3.0
 }
 
 impl AudioComponent for Distortion {
     fn tick(&mut self, in_frame: f32) -> f32 {
-        let mut output = in_frame * 10.0_f32.powf(2.0 * self.drive) + self.offset;
-        output = output.clamp(-1.0, 1.0); //limit output in [-1.0, 1.0]
-        output = self.cubic(output);
-        output * self.gain
+        soft_clip(in_frame * 10.0_f32.powf(2.0 * self.drive) + self.offset) * self.gain
+    }
+    fn process_block(&mut self, input: &[f32], output: &mut [f32]) {
+        crate::check_block(input, output);
+        let pre_gain = 10.0_f32.powf(2.0 * self.drive); // computed once per block instead of per frame.
+        for (in_frame, out_frame) in input.iter().zip(output.iter_mut()) {
+            *out_frame = soft_clip(in_frame * pre_gain + self.offset) * self.gain;
+        }
     }
 }
@@ -17,6 +17,12 @@ impl Dummy {
     }
 }
 
+impl Default for Dummy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AudioComponent for Dummy {
     fn tick(&mut self, in_frame: f32) -> f32 {
         in_frame
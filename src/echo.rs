@@ -76,4 +76,18 @@ impl AudioComponent for Echo {
         let delayed_frame = self.delay.tick(in_frame + self.feedback * self.delay.read());
         self.dry * in_frame + self.wet * delayed_frame
     }
+    fn process_block(&mut self, input: &[f32], output: &mut [f32]) {
+        crate::check_block(input, output);
+        let mut start = 0;
+        while start < input.len() {
+            let segment = self.delay.next_segment(input.len() - start);
+            let end = start + segment.len();
+            let frames = input[start..end].iter().zip(output[start..end].iter_mut());
+            for ((in_frame, out_frame), delayed_frame) in frames.zip(segment.iter_mut()) {
+                *out_frame = self.dry * in_frame + self.wet * *delayed_frame;
+                *delayed_frame = in_frame + self.feedback * *delayed_frame;
+            }
+            start = end;
+        }
+    }
 }
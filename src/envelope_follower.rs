@@ -0,0 +1,172 @@
+//! The `EnvelopeFollower` component tracks the amplitude of its input, for dynamics effects (compressors, gates...)
+//! and meters. The detected signal is smoothed by a `Smooth` with separate attack (rising) and release (falling)
+//! time constants (seconds, the output reaches 63% of a step after the time, 0.0 -> immediate).
+//!
+//! Detector modes (`DetectorMode`):
+//! - `Peak`: absolute value of the input.
+//! - `Rms`: square of the input, the square root being taken after the smoothing (a sinusoid of amplitude 1.0 gives 0.707).
+//! - `TruePeak`: absolute value of the input 4x oversampled (8 taps windowed sinc per phase), which catches the peaks
+//!   between the frames (error < 2% up to sample_rate / 4, latency 4 frames).
+//!
+//! The output is linear or in dB (`EnvelopeOutput`), the dB output being limited to `MIN_DB`.
+//!
+//! # Examples:
+//! A sinusoid at sample_rate / 4 with a phase of pi / 4 has frames of +/- 0.707 but a peak of 1.0:
+//! ```rust
+//! # use mydsp_jack::envelope_follower::{EnvelopeFollower, DetectorMode};
+//! # use mydsp_jack::AudioComponent;
+//! let level = |mode: DetectorMode| {
+//!     let mut follower1 = EnvelopeFollower::new(48000, mode, 0.0, 0.1);
+//!     let input = |n: usize| (std::f32::consts::PI * (0.5 * n as f32 + 0.25)).sin();
+//!     (0..4800).map(|n| follower1.tick(input(n))).last().unwrap()
+//! };
+//! debug_assert!((level(DetectorMode::Peak) - 0.707).abs() < 1e-3);
+//! debug_assert!((level(DetectorMode::Rms) - 0.707).abs() < 1e-3);
+//! debug_assert!((level(DetectorMode::TruePeak) - 1.0).abs() < 0.02);
+//! ```
+//! Attack and release at 1kHz: attack 10 frames, release 100 frames, dB output:
+//! ```rust
+//! # use mydsp_jack::envelope_follower::{EnvelopeFollower, DetectorMode, EnvelopeOutput, MIN_DB};
+//! # use mydsp_jack::AudioComponent;
+//! let mut follower2 = EnvelopeFollower::new(1000, DetectorMode::Peak, 0.01, 0.1);
+//! follower2.set_output(EnvelopeOutput::Decibels);
+//! debug_assert_eq!(follower2.tick(0.0), MIN_DB);
+//! let attack: Vec<f32> = (0..10).map(|_| follower2.tick(-1.0)).collect();
+//! let release: Vec<f32> = (0..100).map(|_| follower2.tick(0.0)).collect();
+//! let db = |x: f32| 20.0 * x.log10();
+//! debug_assert!((attack[9] - db(1.0 - (-1.0_f32).exp())).abs() < 1e-3); // 63%, -4dB
+//! debug_assert!((release[99] - db((1.0 - (-1.0_f32).exp()) * (-1.0_f32).exp())).abs() < 1e-3); // 37% of -4dB, -12.6dB
+//! ```
+use std::f32::consts::PI;
+use crate::AudioComponent;
+use crate::smooth::Smooth;
+
+/// Lowest output (dB) of `EnvelopeOutput::Decibels`, returned for silence.
+pub const MIN_DB: f32 = -120.0;
+/// Taps of each phase of the true peak interpolation.
+const TRUE_PEAK_TAPS: usize = 8;
+/// Oversampling of the true peak detector.
+const TRUE_PEAK_OVERSAMPLING: usize = 4;
+
+/// What the `EnvelopeFollower` measures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DetectorMode {
+    /// Absolute value of the frames.
+    #[default]
+    Peak,
+    /// Root mean square.
+    Rms,
+    /// Absolute value of the 4x oversampled signal (inter-sample peaks).
+    TruePeak,
+}
+
+/// Unit of the output of the `EnvelopeFollower`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EnvelopeOutput {
+    #[default]
+    Linear,
+    /// 20 * log10(level), limited to `MIN_DB`.
+    Decibels,
+}
+
+#[derive(Debug)]
+pub struct EnvelopeFollower {
+    smooth: Smooth,
+    sample_rate: usize,
+    mode: DetectorMode,
+    output: EnvelopeOutput,
+    history: [f32; TRUE_PEAK_TAPS], // last input frames, the newest last.
+    true_peak_coefficients: [[f32; TRUE_PEAK_TAPS]; TRUE_PEAK_OVERSAMPLING],
+}
+
+impl EnvelopeFollower {
+    /// Returns an `EnvelopeFollower` instance with a detector `mode` and `attack` and `release` times (seconds).
+    ///
+    /// Default: linear output.
+    ///
+    /// # Panics
+    /// The function panics if `attack` or `release` is negative.
+    /// ```rust, should_panic
+    /// # use mydsp_jack::envelope_follower::{EnvelopeFollower, DetectorMode};
+    /// let follower_panic = EnvelopeFollower::new(48000, DetectorMode::Rms, -0.01, 0.1);
+    /// ```
+    pub fn new(sample_rate: usize, mode: DetectorMode, attack: f32, release: f32) -> Self {
+        let mut follower = EnvelopeFollower {
+            smooth: Smooth::new(0.0),
+            sample_rate,
+            mode,
+            output: EnvelopeOutput::default(),
+            history: [0.0; TRUE_PEAK_TAPS],
+            true_peak_coefficients: [[0.0; TRUE_PEAK_TAPS]; TRUE_PEAK_OVERSAMPLING],
+        };
+        follower.set_times(attack, release);
+        // Hann windowed sinc, phase p interpolates at p / 4 frame after the frame `TRUE_PEAK_TAPS / 2` frames old.
+        for (phase, coefficients) in follower.true_peak_coefficients.iter_mut().enumerate() {
+            for (tap, coefficient) in coefficients.iter_mut().enumerate() {
+                let x = tap as f32 - (TRUE_PEAK_TAPS / 2 - 1) as f32 - phase as f32 / TRUE_PEAK_OVERSAMPLING as f32;
+                let sinc = if x == 0.0 { 1.0 } else { (PI * x).sin() / (PI * x) };
+                *coefficient = sinc * (0.5 + 0.5 * (PI * x / (TRUE_PEAK_TAPS / 2) as f32).cos());
+            }
+            let sum: f32 = coefficients.iter().sum();
+            coefficients.iter_mut().for_each(|coefficient| *coefficient /= sum);
+        }
+        follower
+    }
+    /// Modifies the detector mode, the envelope restarts from 0.0.
+    pub fn set_mode(&mut self, mode: DetectorMode) {
+        self.mode = mode;
+        self.reset();
+    }
+    /// Returns the detector mode.
+    pub fn mode(&self) -> DetectorMode {
+        self.mode
+    }
+    /// Modifies the `attack` and `release` times (seconds).
+    pub fn set_times(&mut self, attack: f32, release: f32) {
+        if attack < 0.0 || release < 0.0 {
+            panic!("!!!EnvelopeFollower panic: attack and release must be non-negative");
+        }
+        self.smooth.set_attack_release(Smooth::time_to_factor(self.sample_rate, attack), Smooth::time_to_factor(self.sample_rate, release));
+    }
+    /// Modifies the unit of the output.
+    pub fn set_output(&mut self, output: EnvelopeOutput) {
+        self.output = output;
+    }
+    /// Returns the current level (linear), whatever the output unit.
+    pub fn level(&self) -> f32 {
+        match self.mode {
+            DetectorMode::Rms => self.smooth.value().sqrt(),
+            DetectorMode::Peak | DetectorMode::TruePeak => self.smooth.value(),
+        }
+    }
+    /// Clears the envelope and the true peak history.
+    pub fn reset(&mut self) {
+        self.smooth.reset();
+        self.history = [0.0; TRUE_PEAK_TAPS];
+    }
+    /// Returns the largest absolute value of the 4x oversampled history.
+    fn true_peak(&self) -> f32 {
+        self.true_peak_coefficients.iter()
+            .map(|coefficients| coefficients.iter().zip(self.history.iter()).map(|(c, x)| c * x).sum::<f32>().abs())
+            .fold(0.0, f32::max)
+    }
+}
+
+impl AudioComponent for EnvelopeFollower {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        let detected = match self.mode {
+            DetectorMode::Peak => in_frame.abs(),
+            DetectorMode::Rms => in_frame * in_frame,
+            DetectorMode::TruePeak => {
+                self.history.copy_within(1.., 0);
+                self.history[TRUE_PEAK_TAPS - 1] = in_frame;
+                self.true_peak()
+            }
+        };
+        self.smooth.tick(detected);
+        match self.output {
+            EnvelopeOutput::Linear => self.level(),
+            EnvelopeOutput::Decibels => (20.0 * self.level().log10()).max(MIN_DB),
+        }
+    }
+}
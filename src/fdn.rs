@@ -0,0 +1,284 @@
+//! The `FdnReverb` component is a feedback delay network reverb: 4, 8 or 16 delay lines in parallel,
+//! whose outputs are filtered, mixed by an orthogonal (lossless) matrix and fed back to their inputs.
+//!
+//! * the lengths of the lines are prime numbers of frames spread between 25ms and 80ms,
+//!   so that the echoes do not coincide.
+//! * `FdnMatrix::Householder` (I - 2/N) or `FdnMatrix::Hadamard` (fast Walsh-Hadamard transform, denser mixing).
+//! * each line has a 3-band decay filter (split by two one-pole low-passes at the crossover frequencies)
+//!   whose gains give the target RT60 (time in seconds for a decay of 60dB) in the low, mid and high bands:
+//!   gain = 10^(-3 * length / (sample_rate * RT60)).
+//! * the lengths are slowly modulated by sinusoids of different rates (fractional `DelayVar`),
+//!   which avoids the metallic ringing of the resonant modes.
+//!
+//! The input is mono: `tick_stereo()` returns `[left, right]` (even lines -> left, odd lines -> right),
+//! `tick()` returns their average and `tick_frame()` (`MultiAudioComponent<2>`) reverberates the average of a stereo input.
+//!
+//! # Examples:
+//! RT60: the energy decay curve (Schroeder backward integration) of the impulse response
+//! drops by 60dB in about the target time with each matrix and number of lines: within 10% without modulation,
+//! and 5% to 20% shorter with the default modulation (loss of the linear interpolation, see `set_modulation()`):
+//! ```rust
+//! # use mydsp_jack::fdn::{FdnReverb, FdnMatrix};
+//! # use mydsp_jack::AudioComponent;
+//! /// Estimates the RT60 (seconds) from the slope of the energy decay curve between -5dB and -25dB.
+//! fn estimate_rt60(response: &[f32], sample_rate: f32) -> f32 {
+//!     let mut energy: Vec<f64> = response.iter().map(|x| (*x as f64).powi(2)).collect();
+//!     for n in (0..energy.len() - 1).rev() { energy[n] += energy[n + 1]; } // backward integration
+//!     let decay_db = |n: usize| 10.0 * (energy[n] / energy[0]).log10();
+//!     let start = (0..energy.len()).find(|n| decay_db(*n) < -5.0).unwrap();
+//!     let end = (0..energy.len()).find(|n| decay_db(*n) < -25.0).unwrap();
+//!     60.0 / 20.0 * (end - start) as f32 / sample_rate
+//! }
+//! for (lines, matrix) in [(4, FdnMatrix::Householder), (8, FdnMatrix::Hadamard), (16, FdnMatrix::Hadamard)] {
+//!     let rt60 = |modulation_depth: Option<f32>| {
+//!         let mut fdn1 = FdnReverb::new(8000, lines, matrix, 1.0, 1.0, 1.0, 1.0);
+//!         if let Some(depth) = modulation_depth { fdn1.set_modulation(0.5, depth); }
+//!         let response: Vec<f32> = (0..24000).map(|n| fdn1.tick(if n == 0 { 1.0 } else { 0.0 })).collect();
+//!         estimate_rt60(&response, 8000.0)
+//!     };
+//!     debug_assert!((rt60(Some(0.0)) - 1.0).abs() < 0.1);
+//!     let default_rt60 = rt60(None);
+//!     debug_assert!(default_rt60 > 0.8 && default_rt60 < 0.95);
+//! }
+//! ```
+//! Frequency-dependent decay: with a long RT60 in the low band and a short one in the high band,
+//! a low tone rings much longer than a high tone:
+//! ```rust
+//! # use mydsp_jack::fdn::{FdnReverb, FdnMatrix};
+//! # use mydsp_jack::AudioComponent;
+//! fn tail_level(frequency: f32) -> f32 {
+//!     let mut fdn2 = FdnReverb::new(16000, 8, FdnMatrix::Hadamard, 2.0, 1.0, 0.2, 1.0);
+//!     let mut level = 0.0_f32;
+//!     for n in 0..16000 {
+//!         let in_frame = if n < 3200 { (2.0 * std::f32::consts::PI * frequency * n as f32 / 16000.0).sin() } else { 0.0 };
+//!         let out_frame = fdn2.tick(in_frame);
+//!         if n >= 11200 { level = level.max(out_frame.abs()); } // 0.5s after the end of the tone
+//!     }
+//!     level
+//! }
+//! let (low, high) = (tail_level(100.0), tail_level(5000.0));
+//! debug_assert!(low > 30.0 * high); // about 38dB (one-pole crossovers)
+//! ```
+use std::f32::consts::PI;
+use crate::AudioComponent;
+use crate::delay_var::DelayVar;
+use crate::multi::{Frame, MultiAudioComponent};
+use crate::phasor::Phasor;
+
+/// Shortest and longest delay lines (ms).
+const MIN_LENGTH_MS: f32 = 25.0;
+const MAX_LENGTH_MS: f32 = 80.0;
+/// Maximum modulation depth (ms).
+pub const MAX_MODULATION_DEPTH_MS: f32 = 2.0;
+
+/// Feedback matrix of `FdnReverb`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FdnMatrix {
+    /// I - 2/N * ones, every line is fed by all the others with the same gain.
+    Householder,
+    /// Normalized Hadamard matrix, computed with a fast Walsh-Hadamard transform.
+    Hadamard,
+}
+
+/// One delay line of the network with its decay filter and its modulation.
+#[derive(Debug)]
+struct FdnLine {
+    delay: DelayVar,
+    length: f32, // loop length (frames) without modulation.
+    gains: [f32; 3], // decay gains of the low, mid and high bands.
+    lowpass_low: f32, // state of the low/mid crossover.
+    lowpass_high: f32, // state of the mid/high crossover.
+    lfo_phasor: Phasor,
+    output: f32, // last output of the delay line.
+}
+
+#[derive(Debug)]
+pub struct FdnReverb {
+    lines: Vec<FdnLine>,
+    matrix: FdnMatrix,
+    scratch: Vec<f32>, // filtered outputs of the lines, mixed in place.
+    sample_rate: usize,
+    rt60: [f32; 3],
+    crossover_coefs: [f32; 2], // one-pole coefficients of the crossovers.
+    modulation_depth: f32, // frames
+    mix: f32,
+}
+
+impl FdnReverb {
+    /// Returns an `FdnReverb` instance with `lines` delay lines (4, 8 or 16), a feedback `matrix`,
+    /// the RT60 (seconds) of the low, mid and high bands, and a dry/wet `mix` in [0.0, 1.0].
+    ///
+    /// The crossovers are at 500Hz and 4000Hz (at most sample_rate / 4) (`set_crossovers()`),
+    /// the lengths are modulated by 0.5ms at about 0.5Hz (`set_modulation()`).
+    ///
+    /// # Panics
+    /// The function panics if `lines` is not 4, 8 or 16, if an RT60 is not positive or if `mix` is not in the range [0.0, 1.0].
+    /// ```rust, should_panic
+    /// # use mydsp_jack::fdn::{FdnReverb, FdnMatrix};
+    /// let fdn_panic = FdnReverb::new(48000, 6, FdnMatrix::Householder, 2.0, 1.5, 1.0, 0.3);
+    /// ```
+    pub fn new(sample_rate: usize, lines: usize, matrix: FdnMatrix, rt60_low: f32, rt60_mid: f32, rt60_high: f32, mix: f32) -> Self {
+        if ![4, 8, 16].contains(&lines) {
+            panic!("!!!FdnReverb panic: the number of lines must be 4, 8 or 16");
+        }
+        let frames = |ms: f32| ms * 0.001 * sample_rate as f32;
+        let max_frames = frames(MAX_LENGTH_MS + MAX_MODULATION_DEPTH_MS).ceil() as usize + 2;
+        let mut fdn = FdnReverb {
+            lines: (0..lines)
+                .map(|i| {
+                    let ratio = i as f32 / (lines - 1) as f32;
+                    let length = next_prime(frames(MIN_LENGTH_MS * (MAX_LENGTH_MS / MIN_LENGTH_MS).powf(ratio)) as usize);
+                    let mut lfo_phasor = Phasor::with_frequency(sample_rate, 0.5);
+                    lfo_phasor.set_phase(ratio); // spread initial phases.
+                    FdnLine {
+                        delay: DelayVar::new(max_frames, 0),
+                        length: length as f32,
+                        gains: [0.0; 3],
+                        lowpass_low: 0.0,
+                        lowpass_high: 0.0,
+                        lfo_phasor,
+                        output: 0.0,
+                    }
+                })
+                .collect(),
+            matrix,
+            scratch: vec![0.0; lines],
+            sample_rate,
+            rt60: [0.0; 3],
+            crossover_coefs: [0.0; 2],
+            modulation_depth: 0.0,
+            mix: 0.0,
+        };
+        fdn.set_crossovers(500.0, 4000.0_f32.min(0.25 * sample_rate as f32));
+        fdn.set_modulation(0.5, 0.5);
+        fdn.set_rt60(rt60_low, rt60_mid, rt60_high);
+        fdn.set_mix(mix);
+        fdn
+    }
+    /// Modifies the RT60 (seconds) of the low, mid and high bands.
+    ///
+    /// **memo**: the network is guaranteed stable when rt60_low >= rt60_mid >= rt60_high (the usual case),
+    /// otherwise the gain of the decay filters can slightly exceed the largest band gain around the crossovers.
+    pub fn set_rt60(&mut self, rt60_low: f32, rt60_mid: f32, rt60_high: f32) {
+        if rt60_low <= 0.0 || rt60_mid <= 0.0 || rt60_high <= 0.0 {
+            panic!("!!!FdnReverb panic: RT60 must be positive");
+        }
+        self.rt60 = [rt60_low, rt60_mid, rt60_high];
+        let sample_rate = self.sample_rate as f32;
+        for line in self.lines.iter_mut() {
+            for (gain, rt60) in line.gains.iter_mut().zip(self.rt60) {
+                *gain = 10.0_f32.powf(-3.0 * line.length / (sample_rate * rt60));
+            }
+        }
+    }
+    /// Modifies the crossover frequencies (Hz) between the low and mid bands, and between the mid and high bands.
+    pub fn set_crossovers(&mut self, low_mid: f32, mid_high: f32) {
+        if low_mid <= 0.0 || mid_high <= low_mid || mid_high >= self.sample_rate as f32 / 2.0 {
+            panic!("!!!FdnReverb panic: crossovers must be such that 0 < low_mid < mid_high < sample_rate / 2");
+        }
+        let coef = |frequency: f32| (-2.0 * PI * frequency / self.sample_rate as f32).exp();
+        self.crossover_coefs = [coef(low_mid), coef(mid_high)];
+    }
+    /// Modifies the modulation of the lengths: `rate` (Hz) of the slowest line and peak-to-peak `depth` (ms)
+    /// in [0, `MAX_MODULATION_DEPTH_MS`], 0.0 -> no modulation.
+    ///
+    /// **memo**: the fractional delays are read with linear interpolation (a low-pass whose cutoff moves with the
+    /// fraction), so any modulation shortens the RT60 by 5% to 20%, nearly whatever the depth. The loss depends on
+    /// the frequency, so it is not compensated by the decay gains (which would exceed 1.0 at low frequencies).
+    pub fn set_modulation(&mut self, rate: f32, depth: f32) {
+        if !(0.0..=MAX_MODULATION_DEPTH_MS).contains(&depth) {
+            panic!("!!!FdnReverb panic: depth must be in the range [0, {}] ms", MAX_MODULATION_DEPTH_MS);
+        }
+        self.modulation_depth = depth * 0.001 * self.sample_rate as f32;
+        let nlines = self.lines.len() as f32;
+        for (i, line) in self.lines.iter_mut().enumerate() {
+            line.lfo_phasor.set_frequency(rate * (1.0 + 0.5 * i as f32 / nlines)); // different rates.
+        }
+    }
+    /// Modifies the dry/wet mix in [0.0, 1.0].
+    pub fn set_mix(&mut self, mix: f32) {
+        if !(0.0..=1.0).contains(&mix) {
+            panic!("!!!FdnReverb panic: mix must be in the range [0.0, 1.0]");
+        }
+        self.mix = mix;
+    }
+    /// Returns the number of delay lines.
+    pub fn lines(&self) -> usize {
+        self.lines.len()
+    }
+    /// Processes one mono frame and returns the stereo frame `[left, right]`.
+    pub fn tick_stereo(&mut self, in_frame: f32) -> Frame<2> {
+        let [coef_low, coef_high] = self.crossover_coefs;
+        // decay filters of the outputs of the previous frame.
+        for (line, filtered) in self.lines.iter_mut().zip(self.scratch.iter_mut()) {
+            line.lowpass_low = (1.0 - coef_low) * line.output + coef_low * line.lowpass_low;
+            line.lowpass_high = (1.0 - coef_high) * line.output + coef_high * line.lowpass_high;
+            let low = line.lowpass_low;
+            let mid = line.lowpass_high - line.lowpass_low;
+            let high = line.output - line.lowpass_high;
+            *filtered = line.gains[0] * low + line.gains[1] * mid + line.gains[2] * high;
+        }
+        match self.matrix {
+            FdnMatrix::Householder => householder(&mut self.scratch),
+            FdnMatrix::Hadamard => hadamard(&mut self.scratch),
+        }
+        let nlines = self.lines.len();
+        let input_gain = 1.0 / (nlines as f32).sqrt();
+        let mut wet = [0.0; 2];
+        for (i, (line, feedback)) in self.lines.iter_mut().zip(self.scratch.iter()).enumerate() {
+            let lfo = (2.0 * PI * line.lfo_phasor.tick(0.0)).sin();
+            // `DelayVar` reads after writing, which adds 1 frame to the loop.
+            line.delay.set_delay(line.length - 1.0 + 0.5 * self.modulation_depth * lfo);
+            let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+            line.output = line.delay.tick(sign * input_gain * in_frame + feedback);
+            wet[i % 2] += line.output;
+        }
+        let wet_gain = 2.0 / nlines as f32;
+        let dry = (1.0 - self.mix) * in_frame;
+        [dry + self.mix * wet_gain * wet[0], dry + self.mix * wet_gain * wet[1]]
+    }
+}
+
+/// Multiplies `frame` in place by the Householder matrix I - 2/N * ones.
+fn householder(frame: &mut [f32]) {
+    let sum = frame.iter().sum::<f32>() * 2.0 / frame.len() as f32;
+    frame.iter_mut().for_each(|x| *x -= sum);
+}
+
+/// Multiplies `frame` (length power of 2) in place by the normalized Hadamard matrix.
+fn hadamard(frame: &mut [f32]) {
+    let n = frame.len();
+    let mut half = 1;
+    while half < n {
+        for block in (0..n).step_by(2 * half) {
+            for i in block..block + half {
+                let (a, b) = (frame[i], frame[i + half]);
+                frame[i] = a + b;
+                frame[i + half] = a - b;
+            }
+        }
+        half *= 2;
+    }
+    let norm = 1.0 / (n as f32).sqrt();
+    frame.iter_mut().for_each(|x| *x *= norm);
+}
+
+/// Returns the smallest prime number >= `n`.
+#[allow(clippy::manual_is_multiple_of)] // `is_multiple_of()` needs Rust 1.87.
+fn next_prime(n: usize) -> usize {
+    let is_prime = |k: usize| k >= 2 && (2..).take_while(|d| d * d <= k).all(|d| k % d != 0);
+    (n..).find(|k| is_prime(*k)).unwrap()
+}
+
+impl AudioComponent for FdnReverb {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        let [left, right] = self.tick_stereo(in_frame);
+        0.5 * (left + right)
+    }
+}
+
+impl MultiAudioComponent<2> for FdnReverb {
+    fn tick_frame(&mut self, in_frame: Frame<2>) -> Frame<2> {
+        self.tick_stereo(0.5 * (in_frame[0] + in_frame[1]))
+    }
+}
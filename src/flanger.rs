@@ -1,38 +1,134 @@
-use crate::sine_table::SineTable;
-use crate::phasor::Phasor;
-use crate::echo::Echo;
-use crate::sine::SineWave;
-// use crate::dummy::Dummy;
+//! The `Flanger` component mixes the input with a copy delayed by a few milliseconds,
+//! the delay being swept by a sinusoidal LFO, which creates moving comb-filter notches.
+//!
+//! delay = base delay + depth * (lfo * 0.5 + 0.5), read with a fractional (interpolated) `DelayVar`.
+//! output = (1 - mix) * input + mix * delayed, and delayed * feedback is sent back into the delay.
+//!
+//! With mix = 0.5 and no feedback, the notches are at frequencies (2k + 1) / (2 * delay).
+//!
+//! # Examples:
+//! Comb-notch sweep: a 1kHz sinusoid is cancelled when the delay crosses 0.5ms (and 1.5ms),
+//! and doubled when it crosses 1ms:
+//! ```rust
+//! # use mydsp_jack::flanger::Flanger;
+//! # use mydsp_jack::sine_table::SineTable;
+//! # use mydsp_jack::AudioComponent;
+//! use once_cell::sync::Lazy;
+//! static SINETABLE1: Lazy<SineTable> = Lazy::new(|| SineTable::new(4096));
+//! // rate 1Hz, delay from 0.1ms to 2.1ms, no feedback, mix 0.5
+//! let mut flanger1 = Flanger::new(48000, &SINETABLE1, 1.0, 0.1, 2.0, 0.0, 0.5);
+//! let mut rms_min = f32::MAX;
+//! let mut rms_max = 0.0_f32;
+//! for window in 0..1000 { // windows of 1ms = 1 period of 1kHz
+//!     let mut energy = 0.0;
+//!     for i in 0..48 {
+//!         let in_frame = (2.0 * std::f32::consts::PI * 1000.0 * (window * 48 + i) as f32 / 48000.0).sin();
+//!         energy += flanger1.tick(in_frame).powi(2);
+//!     }
+//!     let rms = (energy / 48.0).sqrt();
+//!     if window > 10 { // after the delay line is filled
+//!         rms_min = rms_min.min(rms);
+//!         rms_max = rms_max.max(rms);
+//!     }
+//! }
+//! debug_assert!(rms_min < 0.1); // notch
+//! debug_assert!(rms_max > 0.65); // input rms = 0.707
+//! ```
 use crate::AudioComponent;
-// TODO: 
-// * bug ?
+use crate::delay_var::DelayVar;
+use crate::phasor::Phasor;
+use crate::sine_table::SineTable;
+
+/// Maximum of base delay + depth (ms).
+pub const MAX_DELAY_MS: f32 = 20.0;
+
+#[derive(Debug)]
 pub struct Flanger {
-    sine_wave: SineWave, // TODO: WN etc.
-    echo: Echo,
-    nframes_delay: usize,
-    lfo_index: f32,
-    depth: f32,
+    sine_table: &'static SineTable,
+    lfo_phasor: Phasor,
+    delay: DelayVar,
+    sample_rate: usize,
+    base_delay: f32, // frames
+    depth: f32, // frames
+    feedback: f32,
+    mix: f32,
+    delayed_frame: f32, // last output of the delay, for feedback.
 }
 
 impl Flanger {
-    pub fn new(sine_table: &SineTable, phasor: Phasor, gain:f32, lfo_index: f32, depth: f32, nframes_delay: usize, feedback: f32, dry: f32, wet: f32) -> Self {
-        let sine_wave = SineWave::new(sine_table, phasor, gain);
-        // let echo = Echo::new(nframes_delay, feedback, port_pairs, Box::new(Dummy::new())); //in_app move to echo
-        let echo = Echo::new(nframes_delay, feedback, dry, wet);
-        Flanger {
-            sine_wave,
-            echo,
-            nframes_delay,
-            lfo_index,
-            depth,
+    /// Returns a `Flanger` instance with an LFO of `rate` (Hz), a `base_delay` and a `depth` in milliseconds,
+    /// a `feedback` in (-1.0, 1.0) and a dry/wet `mix` in [0.0, 1.0].
+    ///
+    /// # Panics
+    /// The function panics if `base_delay` or `depth` is negative, if `base_delay + depth` > `MAX_DELAY_MS`,
+    /// if `feedback` is not in the range (-1.0, 1.0) or if `mix` is not in the range [0.0, 1.0] (NaN included),
+    /// the setters too.
+    /// ```rust, should_panic
+    /// # use mydsp_jack::flanger::Flanger;
+    /// # use mydsp_jack::sine_table::SineTable;
+    /// use once_cell::sync::Lazy;
+    /// static SINETABLE1: Lazy<SineTable> = Lazy::new(|| SineTable::new(4096));
+    /// let flanger_panic = Flanger::new(48000, &SINETABLE1, 0.5, 1.0, 2.0, 1.0, 0.5);
+    /// ```
+    /// ```rust, should_panic
+    /// # use mydsp_jack::flanger::Flanger;
+    /// # use mydsp_jack::sine_table::SineTable;
+    /// use once_cell::sync::Lazy;
+    /// static SINETABLE1: Lazy<SineTable> = Lazy::new(|| SineTable::new(4096));
+    /// let mut flanger_nan = Flanger::new(48000, &SINETABLE1, 0.5, 1.0, 2.0, 0.5, 0.5);
+    /// flanger_nan.set_delay(f32::NAN, 2.0);
+    /// ```
+    pub fn new(sample_rate: usize, sine_table: &'static SineTable, rate: f32, base_delay: f32, depth: f32, feedback: f32, mix: f32) -> Self {
+        let max_frames = (MAX_DELAY_MS * 0.001 * sample_rate as f32).ceil() as usize + 2;
+        let mut flanger = Flanger {
+            sine_table,
+            lfo_phasor: Phasor::with_frequency(sample_rate, rate),
+            delay: DelayVar::new(max_frames, 0),
+            sample_rate,
+            base_delay: 0.0,
+            depth: 0.0,
+            feedback: 0.0,
+            mix: 0.0,
+            delayed_frame: 0.0,
+        };
+        flanger.set_delay(base_delay, depth);
+        flanger.set_feedback(feedback);
+        flanger.set_mix(mix);
+        flanger
+    }
+    /// Modifies the LFO rate (Hz).
+    pub fn set_rate(&mut self, rate: f32) {
+        self.lfo_phasor.set_frequency(rate);
+    }
+    /// Modifies the `base_delay` and the `depth` (ms) of the sweep.
+    pub fn set_delay(&mut self, base_delay: f32, depth: f32) {
+        if !(0.0..=MAX_DELAY_MS).contains(&base_delay) || !(0.0..=MAX_DELAY_MS - base_delay).contains(&depth) {
+            panic!("!!!Flanger panic: base_delay and depth must be non-negative and base_delay + depth <= {} ms", MAX_DELAY_MS);
+        }
+        self.base_delay = base_delay * 0.001 * self.sample_rate as f32;
+        self.depth = depth * 0.001 * self.sample_rate as f32;
+    }
+    /// Modifies the feedback in (-1.0, 1.0) (negative values move the notches).
+    pub fn set_feedback(&mut self, feedback: f32) {
+        if !(-1.0 < feedback && feedback < 1.0) {
+            panic!("!!!Flanger panic: feedback must be in the range (-1.0, 1.0)");
+        }
+        self.feedback = feedback;
+    }
+    /// Modifies the dry/wet mix in [0.0, 1.0] (0.0 -> input only, 1.0 -> delayed only).
+    pub fn set_mix(&mut self, mix: f32) {
+        if !(0.0..=1.0).contains(&mix) {
+            panic!("!!!Flanger panic: mix must be in the range [0.0, 1.0]");
         }
+        self.mix = mix;
     }
 }
 
 impl AudioComponent for Flanger {
     fn tick(&mut self, in_frame: f32) -> f32 {
-        let lfo = self.sine_wave.tick(in_frame) * 0.5 + 0.5;
-        self.echo.set_delay((self.nframes_delay as f32 * (1.0 - lfo * self.lfo_index)) as usize);
-        self.echo.tick(in_frame) * self.depth
+        let lfo = self.sine_table.get_value(self.lfo_phasor.tick(in_frame)) * 0.5 + 0.5; // [0, 1]
+        self.delay.set_delay(self.base_delay + self.depth * lfo);
+        self.delayed_frame = self.delay.tick(in_frame + self.feedback * self.delayed_frame);
+        (1.0 - self.mix) * in_frame + self.mix * self.delayed_frame
     }
 }
@@ -7,39 +7,66 @@ pub struct Fm {
     c_phasor: Phasor, // carrier
     m_phasor: Phasor, // modulator
     fc: f32, // carrier_frequency
-    mod_index: f32, // modulation index
+    deviation: f32, // frequency deviation (Hz), modulation index = deviation / fm
     gain: f32,
     sine_table: &'static SineTable,
 }
 
 impl Fm {
-    pub fn new(sample_rate: usize, sine_table: &'static SineTable, fc: f32, fm: f32, mod_index: f32, gain: f32) -> Self {
+    /// Returns an `Fm` instance: a sinusoidal carrier of frequency `fc` (Hz) whose frequency is modulated
+    /// by a sinusoid of frequency `fm` (Hz) with a peak `deviation` (Hz), i.e. the instantaneous frequency
+    /// is fc + deviation * sin(2pi * fm * t), and the modulation index is deviation / fm.
+    ///
+    /// # Examples:
+    /// With a modulation index of 2 (deviation 200Hz, fm 100Hz), the amplitudes of the carrier and of the first
+    /// sidebands (fc +/- fm) are the Bessel functions J0(2) = 0.224 and J1(2) = 0.577:
+    /// ```rust
+    /// # use mydsp_jack::fm::Fm;
+    /// # use mydsp_jack::sine_table::SineTable;
+    /// # use mydsp_jack::AudioComponent;
+    /// use once_cell::sync::Lazy;
+    /// static SINETABLE1: Lazy<SineTable> = Lazy::new(|| SineTable::new(4096));
+    /// let mut fm1 = Fm::new(48000, &SINETABLE1, 1000.0, 100.0, 200.0, 1.0);
+    /// let output: Vec<f32> = (0..48000).map(|_| fm1.tick(0.0)).collect();
+    /// let amplitude = |frequency: f32| { // DFT over 1s
+    ///     let (mut re, mut im) = (0.0, 0.0);
+    ///     for (n, x) in output.iter().enumerate() {
+    ///         let angle = 2.0 * std::f64::consts::PI * frequency as f64 * n as f64 / 48000.0;
+    ///         re += *x as f64 * angle.cos();
+    ///         im += *x as f64 * angle.sin();
+    ///     }
+    ///     2.0 * (re * re + im * im).sqrt() / 48000.0
+    /// };
+    /// debug_assert!((amplitude(1000.0) - 0.224).abs() < 0.01);
+    /// debug_assert!((amplitude(1100.0) - 0.577).abs() < 0.01);
+    /// debug_assert!((amplitude(900.0) - 0.577).abs() < 0.01);
+    /// ```
+    pub fn new(sample_rate: usize, sine_table: &'static SineTable, fc: f32, fm: f32, deviation: f32, gain: f32) -> Self {
         Fm {
-            c_phasor: Phasor::new(0.0, fc / sample_rate as f32),
-            m_phasor: Phasor::new(0.0, fm / sample_rate as f32),
+            c_phasor: Phasor::with_frequency(sample_rate, fc),
+            m_phasor: Phasor::with_frequency(sample_rate, fm),
             fc,
-            mod_index,
+            deviation,
             gain,
             sine_table,
         }
     }
-    // pub fn set_fc(&mut self, fc: f32) {
-    //     self.fc = fc;
-    //     self.c_phasor.set_phase_increment(fc / self.sine_table.length() as f32);
-    // }
-    // pub fn set_fm(&mut self, fm: f32) {
-    //     self.m_phasor.set_phase_increment(fm / self.sine_table.length() as f32);
-    // }
+    /// Modifies the carrier frequency (Hz), from the next frame.
+    pub fn set_fc(&mut self, fc: f32) {
+        self.fc = fc;
+    }
+    /// Modifies the modulator frequency (Hz), the deviation is kept (so the modulation index changes).
+    pub fn set_fm(&mut self, fm: f32) {
+        self.m_phasor.set_frequency(fm);
+    }
 }
 
 impl AudioComponent for Fm {
     fn tick(&mut self, in_frame: f32) -> f32 {
         let m_phase = self.m_phasor.tick(in_frame);
         let modulator: f32 = self.sine_table.get_value(m_phase);
-        // let original_increment = self.fc / self.sine_table.length() as f32;
-        self.c_phasor.set_phase_increment((self.fc + modulator * self.mod_index) / self.sine_table.length() as f32);
+        self.c_phasor.set_frequency(self.fc + modulator * self.deviation);
         let c_phase = self.c_phasor.tick(in_frame);
-        // self.c_phasor.set_phase_increment(original_increment);
         self.sine_table.get_value(c_phase) * self.gain
     }
 }
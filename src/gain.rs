@@ -48,4 +48,8 @@ impl AudioComponent for Gain {
     fn tick(&mut self, _in_frame: f32) -> f32 {
         self.gain
     }
+    fn process_block(&mut self, input: &[f32], output: &mut [f32]) {
+        crate::check_block(input, output);
+        output.fill(self.gain);
+    }
 }
@@ -0,0 +1,262 @@
+//! The `Graph` component connects boxed `AudioComponent`s (nodes) declaratively,
+//! so that a whole patch can be ticked with one call, still without depending on `jack-rust`.
+//!
+//! Each node receives the mix of its inputs (sum via `add()` or product via `multiply()`, 0.0 if no input),
+//! and its output can be sent to several nodes (fan-out).
+//! Nodes are ticked in topological order, the order is computed when connecting (not in `tick()`).
+//!
+//! The frame passed to `Graph::tick()` is the output of the `input()` node,
+//! and `Graph::tick()` returns the output of the node set by `set_output()` (`input()` by default).
+//!
+//! # Examples:
+//! Building the patch "SineWave * Gain -> Echo -> Distortion":
+//! ```rust
+//! # use mydsp_jack::graph::Graph;
+//! # use mydsp_jack::AudioComponent;
+//! # use mydsp_jack::{sine::SineWave, sine_table::SineTable, phasor::Phasor, gain::Gain, echo::Echo, distortion::Distortion};
+//! use once_cell::sync::Lazy;
+//! static SINETABLE1: Lazy<SineTable> = Lazy::new(|| SineTable::new(4096));
+//! let mut graph = Graph::new();
+//! let sine = graph.add_node(SineWave::new(&SINETABLE1, Phasor::new(None, 440.0 / 48000.0)));
+//! let gain = graph.add_node(Gain::new(0.5));
+//! let echo = graph.add_node(Echo::new(48000 / 2, 0.6, 0.7, None));
+//! let distortion = graph.add_node(Distortion::new(0.2, 0.0, 0.8));
+//! let product = graph.multiply(&[sine, gain]);
+//! let output = graph.series(&[product, echo, distortion]);
+//! graph.set_output(output);
+//! let out_frame = graph.tick(0.0);
+//! # let mut sine2 = SineWave::new(&SINETABLE1, Phasor::new(None, 440.0 / 48000.0));
+//! # let mut echo2 = Echo::new(48000 / 2, 0.6, 0.7, None);
+//! # let mut distortion2 = Distortion::new(0.2, 0.0, 0.8);
+//! # debug_assert_eq!(out_frame, distortion2.tick(echo2.tick(sine2.tick(0.0) * 0.5)));
+//! # debug_assert_eq!(graph.value(gain), 0.5);
+//! ```
+use crate::AudioComponent;
+use crate::dummy::Dummy;
+
+/// Identifies a node of a `Graph`, returned when adding a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// How a node mixes the outputs of its inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mix {
+    /// Sum of the inputs (`add()`).
+    Add,
+    /// Product of the inputs (`multiply()`).
+    Multiply,
+}
+
+struct Node {
+    component: Box<dyn AudioComponent>,
+    inputs: Vec<NodeId>,
+    mix: Mix,
+    value: f32, // last output of the node.
+}
+
+// manual impl: the boxed components are not `Debug`.
+impl std::fmt::Debug for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Node")
+            .field("inputs", &self.inputs)
+            .field("mix", &self.mix)
+            .field("value", &self.value)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug)]
+pub struct Graph {
+    nodes: Vec<Node>,
+    order: Vec<usize>, // topological order of the nodes.
+    output: NodeId,
+    scratch: Vec<f32>, // outputs of the inputs of a node, allocated when connecting.
+}
+
+impl Graph {
+    /// Returns an empty `Graph` instance, containing only the `input()` node (so it returns its input).
+    ///
+    /// # Examples:
+    /// ```rust
+    /// # use mydsp_jack::graph::Graph;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut graph = Graph::new();
+    /// debug_assert_eq!(graph.tick(0.3), 0.3);
+    /// ```
+    pub fn new() -> Self {
+        let mut graph = Graph {
+            nodes: Vec::new(),
+            order: Vec::new(),
+            output: NodeId(0),
+            scratch: Vec::new(),
+        };
+        graph.add_node(Dummy::new());
+        graph
+    }
+    /// Returns the node whose output is the frame passed to `tick()`.
+    pub fn input(&self) -> NodeId {
+        NodeId(0)
+    }
+    /// Adds a component as a new node (without any connection), its input is mixed with `Mix::Add`.
+    pub fn add_node(&mut self, component: impl AudioComponent + 'static) -> NodeId {
+        self.nodes.push(Node {
+            component: Box::new(component),
+            inputs: Vec::new(),
+            mix: Mix::Add,
+            value: 0.0,
+        });
+        self.order.push(self.nodes.len() - 1); // a node without connection can be ticked last.
+        NodeId(self.nodes.len() - 1)
+    }
+    /// Connects the output of node `from` to the input of node `to`.
+    ///
+    /// # Panics
+    /// The function panics if a node does not belong to the graph or if the connection creates a cycle.
+    /// The connection is not added, so a graph whose panic is caught stays usable:
+    /// ```rust
+    /// # use mydsp_jack::graph::Graph;
+    /// # use mydsp_jack::delay::Delay;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut graph = Graph::new();
+    /// let delay = graph.add_node(Delay::new(1));
+    /// graph.connect(graph.input(), delay);
+    /// graph.set_output(delay);
+    /// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| graph.connect(delay, delay)));
+    /// debug_assert!(result.is_err());
+    /// debug_assert_eq!(graph.tick(1.0), 0.0);
+    /// debug_assert_eq!(graph.tick(2.0), 1.0);
+    /// ```
+    /// ```rust, should_panic
+    /// # use mydsp_jack::graph::Graph;
+    /// # use mydsp_jack::dummy::Dummy;
+    /// let mut graph = Graph::new();
+    /// let a = graph.add_node(Dummy::new());
+    /// let b = graph.add_node(Dummy::new());
+    /// graph.connect(a, b);
+    /// graph.connect(b, a); // panic
+    /// ```
+    pub fn connect(&mut self, from: NodeId, to: NodeId) {
+        self.check_node(from);
+        self.check_node(to);
+        if to == self.input() {
+            panic!("!!!Panic: the input node of a graph cannot have inputs");
+        }
+        self.nodes[to.0].inputs.push(from);
+        if !self.update_order() {
+            // remove the connection before panicking, so the graph stays valid.
+            self.nodes[to.0].inputs.pop();
+            self.update_order();
+            panic!("!!!Panic: connecting node {} to node {} creates a cycle", from.0, to.0);
+        }
+        self.scratch.reserve(self.nodes[to.0].inputs.len());
+    }
+    /// Connects the nodes one after another and returns the last node.
+    pub fn series(&mut self, nodes: &[NodeId]) -> NodeId {
+        for pair in nodes.windows(2) {
+            self.connect(pair[0], pair[1]);
+        }
+        *nodes.last().expect("!!!Panic: series needs at least one node")
+    }
+    /// Adds a node returning the sum of the outputs of `nodes` (parallel mix).
+    /// 
+    /// # Examples:
+    /// Fan-out of the input to a dry path and a delayed path, mixed together:
+    /// ```rust
+    /// # use mydsp_jack::graph::Graph;
+    /// # use mydsp_jack::delay::Delay;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut graph = Graph::new();
+    /// let delay = graph.add_node(Delay::new(1));
+    /// graph.connect(graph.input(), delay);
+    /// let mixed = graph.add(&[graph.input(), delay]);
+    /// graph.set_output(mixed);
+    /// debug_assert_eq!(graph.tick(1.0), 1.0); // 1.0 + 0.0
+    /// debug_assert_eq!(graph.tick(2.0), 3.0); // 2.0 + 1.0
+    /// ```
+    pub fn add(&mut self, nodes: &[NodeId]) -> NodeId {
+        self.mix_node(nodes, Mix::Add)
+    }
+    /// Adds a node returning the product of the outputs of `nodes` (e.g. signal * gain).
+    pub fn multiply(&mut self, nodes: &[NodeId]) -> NodeId {
+        self.mix_node(nodes, Mix::Multiply)
+    }
+    /// Modifies how `node` mixes its inputs.
+    pub fn set_mix(&mut self, node: NodeId, mix: Mix) {
+        self.check_node(node);
+        self.nodes[node.0].mix = mix;
+    }
+    /// Sets the node whose output is returned by `tick()`.
+    pub fn set_output(&mut self, node: NodeId) {
+        self.check_node(node);
+        self.output = node;
+    }
+    /// Returns the last output of `node` (e.g. to monitor a node in the middle of the graph).
+    pub fn value(&self, node: NodeId) -> f32 {
+        self.check_node(node);
+        self.nodes[node.0].value
+    }
+    fn mix_node(&mut self, nodes: &[NodeId], mix: Mix) -> NodeId {
+        let node = self.add_node(Dummy::new());
+        self.set_mix(node, mix);
+        for input in nodes {
+            self.connect(*input, node);
+        }
+        node
+    }
+    fn check_node(&self, node: NodeId) {
+        if node.0 >= self.nodes.len() {
+            panic!("!!!Panic: node {} does not belong to the graph", node.0);
+        }
+    }
+    /// Kahn's algorithm, returns `false` if the graph contains a cycle (then `order` is incomplete).
+    fn update_order(&mut self) -> bool {
+        let mut nb_inputs: Vec<usize> = self.nodes.iter().map(|node| node.inputs.len()).collect();
+        let mut outputs: Vec<Vec<usize>> = vec![Vec::new(); self.nodes.len()];
+        for (i, node) in self.nodes.iter().enumerate() {
+            for input in &node.inputs {
+                outputs[input.0].push(i);
+            }
+        }
+        self.order.clear();
+        let mut ready: Vec<usize> = (0..self.nodes.len()).filter(|i| nb_inputs[*i] == 0).rev().collect();
+        while let Some(i) = ready.pop() {
+            self.order.push(i);
+            for &next in &outputs[i] {
+                nb_inputs[next] -= 1;
+                if nb_inputs[next] == 0 {
+                    ready.push(next);
+                }
+            }
+        }
+        self.order.len() == self.nodes.len()
+    }
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioComponent for Graph {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        for &i in &self.order {
+            if i == 0 {
+                self.nodes[0].value = in_frame; // input node.
+                continue;
+            }
+            self.scratch.clear();
+            for input in &self.nodes[i].inputs {
+                self.scratch.push(self.nodes[input.0].value);
+            }
+            let node = &mut self.nodes[i];
+            let mixed = match (self.scratch.is_empty(), node.mix) {
+                (true, _) => 0.0,
+                (false, Mix::Add) => crate::add(&self.scratch),
+                (false, Mix::Multiply) => crate::multiply(&self.scratch),
+            };
+            node.value = node.component.tick(mixed);
+        }
+        self.nodes[self.output.0].value
+    }
+}
@@ -0,0 +1,108 @@
+//! The `JackRunner` wrapper (only with the `jack` cargo feature) implements `ProcessHandler` of `jack-rust`
+//! for any `AudioComponent` (or `Graph`), so the `impl ProcessHandler for Patch` boilerplate
+//! does not have to be written in each binary crate.
+//!
+//! `JackRunner` owns one mono input port and one mono output port, and processes a whole JACK period
+//! in one call with `process_block()`.
+//!
+//! The component is built by a closure from the sample rate and the period size of the server, and built again
+//! (its state is lost) when the server changes the period size. A JACK server keeps its sample rate while it runs,
+//! the sample rate is read again at each rebuild.
+//!
+//! **memo**: the orphan rule does not apply here because `JackRunner` is defined in `mydsp-jack`.
+//! Without the `jack` feature (default build), `mydsp-jack` is still completely independent of `jack-rust`.
+//!
+//! # Examples:
+//! Running an `Echo` (can be tested with JACK's dummy backend: `jackd -d dummy`, see `tests/jack_dummy.rs`):
+//! ```rust, no_run
+//! # use mydsp_jack::jack_runner::JackRunner;
+//! # use mydsp_jack::echo::Echo;
+//! let (client, _status) = jack::Client::new("mydsp", jack::ClientOptions::NO_START_SERVER).unwrap();
+//! // delay of 0.5s whatever the sample rate of the server.
+//! let runner = JackRunner::new(&client, |sample_rate, _buffer_size| Echo::new(sample_rate / 2, 0.6, 0.7, None)).unwrap();
+//! let in_port = runner.in_port_name();
+//! let out_port = runner.out_port_name();
+//! let active_client = client.activate_async((), runner).unwrap();
+//! active_client.as_client().connect_ports_by_name("system:capture_1", &in_port).unwrap();
+//! active_client.as_client().connect_ports_by_name(&out_port, "system:playback_1").unwrap();
+//! std::thread::sleep(std::time::Duration::from_secs(1));
+//! let (_client, _notifications, runner) = active_client.deactivate().unwrap();
+//! # let _ = runner.component();
+//! ```
+use crate::AudioComponent;
+use jack::{AudioIn, AudioOut, Client, Control, Frames, Port, ProcessHandler, ProcessScope};
+
+pub struct JackRunner<C: AudioComponent> {
+    component: C,
+    new_component: Box<dyn FnMut(usize, usize) -> C + Send>, // (sample_rate, buffer_size) -> component
+    in_port: Port<AudioIn>,
+    out_port: Port<AudioOut>,
+    sample_rate: usize,
+    buffer_size: usize, // frames per JACK period.
+}
+
+impl<C: AudioComponent> JackRunner<C> {
+    /// Returns a `JackRunner` instance owning the ports "in" and "out" registered on `client`,
+    /// and the component returned by `new_component(sample_rate, buffer_size)` (read from `client`).
+    ///
+    /// `new_component` is called again when the period size of the server changes (outside of the real-time processing).
+    ///
+    /// # Errors
+    /// Returns the `jack::Error` of the client if a port cannot be registered.
+    pub fn new(client: &Client, mut new_component: impl FnMut(usize, usize) -> C + Send + 'static) -> Result<Self, jack::Error> {
+        let sample_rate = client.sample_rate();
+        let buffer_size = client.buffer_size() as usize;
+        Ok(JackRunner {
+            component: new_component(sample_rate, buffer_size),
+            new_component: Box::new(new_component),
+            in_port: client.register_port("in", AudioIn)?,
+            out_port: client.register_port("out", AudioOut)?,
+            sample_rate,
+            buffer_size,
+        })
+    }
+    /// Returns the sample rate the component was built for.
+    pub fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+    /// Returns the number of frames per JACK period the component was built for (updated when the server changes it).
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+    /// Returns a reference to the component.
+    pub fn component(&self) -> &C {
+        &self.component
+    }
+    /// Returns a mutable reference to the component.
+    pub fn component_mut(&mut self) -> &mut C {
+        &mut self.component
+    }
+    /// Returns the full name of the input port (e.g. "mydsp:in"), to connect it.
+    pub fn in_port_name(&self) -> String {
+        self.in_port.name().unwrap_or_default()
+    }
+    /// Returns the full name of the output port (e.g. "mydsp:out"), to connect it.
+    pub fn out_port_name(&self) -> String {
+        self.out_port.name().unwrap_or_default()
+    }
+}
+
+impl<C: AudioComponent> ProcessHandler for JackRunner<C> {
+    fn process(&mut self, _: &Client, ps: &ProcessScope) -> Control {
+        let input = self.in_port.as_slice(ps);
+        let output = self.out_port.as_mut_slice(ps);
+        self.component.process_block(input, output);
+        Control::Continue
+    }
+    /// Builds the component again if the period size (or the sample rate) differs from the one it was built for.
+    /// JACK calls it before the first period too, which does not rebuild the component.
+    fn buffer_size(&mut self, client: &Client, size: Frames) -> Control {
+        let sample_rate = client.sample_rate();
+        if size as usize != self.buffer_size || sample_rate != self.sample_rate {
+            self.sample_rate = sample_rate;
+            self.buffer_size = size as usize;
+            self.component = (self.new_component)(sample_rate, self.buffer_size);
+        }
+        Control::Continue
+    }
+}
@@ -1,46 +1,193 @@
-// Karplus Strong
+//! The `KS` component is a Karplus-Strong plucked string voice: a noise burst excites a delay line
+//! whose output is low-pass filtered and fed back (loop delay = one period of the string).
+//!
+//! Loop: output = input + excitation + loop_gain * allpass(lowpass(output delayed by delay_length frames))
+//! * the one-zero low-pass (`brightness`) damps the high harmonics faster than the fundamental,
+//!   its delay is `0.5 * (1 - brightness)` frame.
+//! * the first-order allpass adds the fractional part of the period (between 0.1 and 1.1 frames),
+//!   so that the tuning is not rounded to an integer delay.
+//! * `loop_gain` is computed from the decay time (-60dB after `decay_time` seconds).
+//!
+//! A `KS` instance is one voice, `trigger(velocity)` plucks it again (e.g. from a voice allocator for polyphony).
+//!
+//! # Examples:
+//! Tuning of a 441Hz string (estimated with the autocorrelation of the output):
+//! ```rust
+//! # use mydsp_jack::ks::KS;
+//! # use mydsp_jack::AudioComponent;
+//! fn estimate_frequency(ks: &mut KS, sample_rate: f32, frequency: f32) -> f32 {
+//!     let output: Vec<f32> = (0..6000).map(|_| ks.tick(0.0)).collect();
+//!     let signal = &output[2000..];
+//!     let correlation = |lag: usize| -> f32 { (0..2000).map(|n| signal[n] * signal[n + lag]).sum() };
+//!     let period = (sample_rate / frequency).round() as usize;
+//!     let lag = (period - 3..=period + 3).max_by(|a, b| correlation(*a).total_cmp(&correlation(*b))).unwrap();
+//!     let (r0, r1, r2) = (correlation(lag - 1), correlation(lag), correlation(lag + 1));
+//!     let lag = lag as f32 + 0.5 * (r0 - r2) / (r0 - 2.0 * r1 + r2); // parabolic interpolation
+//!     sample_rate / lag
+//! }
+//! for frequency in [441.0, 1234.5] {
+//!     let mut ks1 = KS::with_seed(48000, frequency, 2.0, 0.5, 1);
+//!     ks1.trigger(1.0);
+//!     let estimated = estimate_frequency(&mut ks1, 48000.0, frequency);
+//!     let cents = 1200.0 * (estimated / frequency).log2();
+//!     debug_assert!(cents.abs() < 3.0);
+//! }
+//! ```
+//! Decay: the energy drops by about 60dB after `decay_time`:
+//! ```rust
+//! # use mydsp_jack::ks::KS;
+//! # use mydsp_jack::AudioComponent;
+//! let mut ks2 = KS::with_seed(48000, 220.0, 0.5, 1.0, 2);
+//! ks2.trigger(1.0);
+//! let output: Vec<f32> = (0..48000).map(|_| ks2.tick(0.0)).collect();
+//! let rms = |frames: &[f32]| (frames.iter().map(|x| x * x).sum::<f32>() / frames.len() as f32).sqrt();
+//! let drop = 20.0 * (rms(&output[24000..26400]) / rms(&output[0..2400])).log10(); // after 0.5s
+//! debug_assert!(drop < -50.0 && drop > -70.0);
+//! ```
 use crate::AudioComponent;
-use crate::one_zero::OneZero;
-// use crate::dummy::Dummy;
-// TODO: 
-// * bug ?
+use crate::noise::WhiteNoise;
+
+/// Lowest frequency of a `KS` voice (Hz), the size of the delay line depends on it.
+pub const MIN_FREQUENCY: f32 = 20.0;
+
+#[derive(Debug)]
 pub struct KS {
-    delay_buffer: Vec<f32>, 
-    read_index: usize, 
-    write_index: usize, 
-    feedback: f32,
-    delay_length: usize,
-    is_triggered: bool,
-    one_zero: OneZero, 
+    delay_buffer: Vec<f32>, // circular buffer, allocated for `MIN_FREQUENCY`.
+    index: usize, // position in delay_buffer to read and write.
+    delay_length: usize, // integer part of the loop delay.
+    allpass_coef: f32, // fractional part of the loop delay.
+    allpass_previous_input: f32,
+    allpass_previous_output: f32,
+    lowpass_coef: f32, // 0.5 * (1 - brightness)
+    lowpass_previous_input: f32,
+    loop_gain: f32,
+    noise: WhiteNoise,
+    burst_frames: usize, // frames of noise excitation left.
+    velocity: f32,
+    sample_rate: usize,
+    frequency: f32,
+    decay_time: f32,
 }
 
 impl KS {
-    pub fn new(sample_rate: usize, feedback: f32, freq: f32, b1: f32, ) -> Self {
-        let delay_length = (sample_rate as f32 / freq).round() as usize;
-        KS {
-            delay_buffer: vec![0.0; delay_length],
-            read_index: delay_length - 1,
-            write_index: 0,
-            feedback,
-            delay_length,
-            is_triggered: false,
-            one_zero: OneZero::new(b1),
+    /// Returns a `KS` voice of a given `frequency` (Hz), `decay_time` (seconds, -60dB) and `brightness` in [0.0, 1.0].
+    ///
+    /// The voice is silent until `trigger()` is called, the noise bursts are seeded from entropy (see `with_seed()`).
+    /// # Panics
+    /// The function panics if `frequency` is not in the range [`MIN_FREQUENCY`, sample_rate / 4],
+    /// if `decay_time` is not positive or if `brightness` is not in the range [0.0, 1.0].
+    /// ```rust, should_panic
+    /// # use mydsp_jack::ks::KS;
+    /// let ks_panic = KS::new(48000, 10.0, 1.0, 0.5);
+    /// ```
+    pub fn new(sample_rate: usize, frequency: f32, decay_time: f32, brightness: f32) -> Self {
+        KS::with_noise(sample_rate, frequency, decay_time, brightness, WhiteNoise::new())
+    }
+    /// Returns a `KS` voice like `new()`, whose noise bursts are reproducible for a given `seed`.
+    ///
+    /// # Examples:
+    /// ```rust
+    /// # use mydsp_jack::ks::KS;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut ks3 = KS::with_seed(48000, 441.0, 1.0, 0.5, 42);
+    /// let mut ks4 = KS::with_seed(48000, 441.0, 1.0, 0.5, 42);
+    /// ks3.trigger(1.0);
+    /// ks4.trigger(1.0);
+    /// debug_assert!((0..4800).all(|_| ks3.tick(0.0) == ks4.tick(0.0)));
+    /// ```
+    pub fn with_seed(sample_rate: usize, frequency: f32, decay_time: f32, brightness: f32, seed: u64) -> Self {
+        KS::with_noise(sample_rate, frequency, decay_time, brightness, WhiteNoise::with_seed(seed))
+    }
+    fn with_noise(sample_rate: usize, frequency: f32, decay_time: f32, brightness: f32, noise: WhiteNoise) -> Self {
+        let mut ks = KS {
+            delay_buffer: vec![0.0; (sample_rate as f32 / MIN_FREQUENCY).ceil() as usize],
+            index: 0,
+            delay_length: 1,
+            allpass_coef: 0.0,
+            allpass_previous_input: 0.0,
+            allpass_previous_output: 0.0,
+            lowpass_coef: 0.0,
+            lowpass_previous_input: 0.0,
+            loop_gain: 0.0,
+            noise,
+            burst_frames: 0,
+            velocity: 0.0,
+            sample_rate,
+            frequency,
+            decay_time,
+        };
+        ks.set_brightness(brightness);
+        ks.set_frequency(frequency);
+        ks.set_decay_time(decay_time);
+        ks
+    }
+    /// Plucks the string with a noise burst of one period, `velocity` in [0.0, 1.0] (clamped) is its amplitude.
+    ///
+    /// The previous vibration is kept (re-pluck), call `reset()` before to mute it.
+    pub fn trigger(&mut self, velocity: f32) {
+        self.velocity = velocity.clamp(0.0, 1.0);
+        self.burst_frames = self.delay_length;
+    }
+    /// Mutes the string (clears the delay line and the filters).
+    pub fn reset(&mut self) {
+        self.delay_buffer.fill(0.0);
+        self.allpass_previous_input = 0.0;
+        self.allpass_previous_output = 0.0;
+        self.lowpass_previous_input = 0.0;
+        self.burst_frames = 0;
+    }
+    /// Modifies the frequency (Hz), the delay line is tuned with its fractional part.
+    pub fn set_frequency(&mut self, frequency: f32) {
+        if frequency < MIN_FREQUENCY || frequency > self.sample_rate as f32 / 4.0 {
+            panic!("!!!KS panic: frequency must be in the range [{}, sample_rate / 4]", MIN_FREQUENCY);
+        }
+        self.frequency = frequency;
+        self.update_tuning();
+        self.set_decay_time(self.decay_time); // loop gain per period depends on the frequency.
+    }
+    /// Modifies the decay time (seconds) for the energy to drop by 60dB.
+    pub fn set_decay_time(&mut self, decay_time: f32) {
+        if decay_time <= 0.0 {
+            panic!("!!!KS panic: decay_time must be positive");
+        }
+        self.decay_time = decay_time;
+        self.loop_gain = 0.001_f32.powf(1.0 / (self.frequency * decay_time));
+    }
+    /// Modifies the brightness in [0.0, 1.0] (0.0 -> classic averaging low-pass, 1.0 -> no low-pass).
+    pub fn set_brightness(&mut self, brightness: f32) {
+        if !(0.0..=1.0).contains(&brightness) {
+            panic!("!!!KS panic: brightness must be in the range [0.0, 1.0]");
         }
+        self.lowpass_coef = 0.5 * (1.0 - brightness);
+        self.update_tuning();
     }
-    pub fn trigger(&mut self) {
-        self.is_triggered = true;
+    /// Splits the period into the delay line, the low-pass delay and the allpass delay (in [0.1, 1.1)).
+    fn update_tuning(&mut self) {
+        let period = self.sample_rate as f32 / self.frequency - self.lowpass_coef;
+        self.delay_length = ((period - 0.1).floor() as usize).clamp(1, self.delay_buffer.len());
+        let fraction = period - self.delay_length as f32;
+        self.allpass_coef = (1.0 - fraction) / (1.0 + fraction);
     }
 }
 
 impl AudioComponent for KS {
     fn tick(&mut self, in_frame: f32) -> f32 {
-        let excitation = if self.is_triggered { self.is_triggered = false; 1.0 } else { 0.0 };
-        let input = in_frame + excitation + self.delay_buffer[self.read_index];
-        let filtered_input = self.one_zero.tick(input);
-        let output = filtered_input + self.feedback * self.delay_buffer[self.read_index]; 
-        self.delay_buffer[self.write_index] = output;
-        self.read_index = (self.read_index + 1) % self.delay_length;
-        self.write_index = (self.write_index + 1) % self.delay_length;
+        let excitation = if self.burst_frames > 0 {
+            self.burst_frames -= 1;
+            self.noise.tick(0.0) * self.velocity
+        } else {
+            0.0
+        };
+        let size = self.delay_buffer.len();
+        let delayed_frame = self.delay_buffer[(self.index + size - self.delay_length) % size];
+        let lowpass = (1.0 - self.lowpass_coef) * delayed_frame + self.lowpass_coef * self.lowpass_previous_input;
+        self.lowpass_previous_input = delayed_frame;
+        let allpass = self.allpass_coef * lowpass + self.allpass_previous_input - self.allpass_coef * self.allpass_previous_output;
+        self.allpass_previous_input = lowpass;
+        self.allpass_previous_output = allpass;
+        let output = in_frame + excitation + self.loop_gain * allpass;
+        self.delay_buffer[self.index] = output;
+        self.index = (self.index + 1) % size;
         output
     }
 }
@@ -0,0 +1,174 @@
+//! The `LadderFilter` component is a Moog-style 4-pole (-24dB per octave) resonant low-pass:
+//! four one-pole stages in series, with the output of the last stage fed back to the input.
+//! The stages are trapezoidal (TPT) one-poles and the feedback loop is solved without a unit delay
+//! (zero-delay feedback), so the cutoff and the resonance stay tuned up to high frequencies.
+//!
+//! The input of the ladder (input - feedback) and the output of each stage on its way to the next stage are saturated
+//! with the cubic soft clipper of `Distortion` (as the transistors of the analog ladder), which bounds the resonance:
+//! the filter self-oscillates (a sinusoid at about the cutoff frequency) when `resonance` reaches 1.0.
+//! The states of the one-poles are not saturated.
+//! `drive` is the gain before the ladder, higher values add harmonics.
+//!
+//! The saturation generates harmonics that can alias at high cutoff frequencies,
+//! `set_oversampling(true)` runs the ladder at twice the sample rate.
+//!
+//! # Examples:
+//! -24dB per octave: a 100Hz sinusoid passes, a 4kHz one (3 octaves above the 500Hz cutoff) is attenuated by more than 60dB:
+//! ```rust
+//! # use mydsp_jack::ladder::LadderFilter;
+//! # use mydsp_jack::AudioComponent;
+//! fn amplitude(ladder: &mut LadderFilter, frequency: f32) -> f32 {
+//!     let mut amplitude = 0.0_f32;
+//!     for n in 0..48000 {
+//!         let out_frame = ladder.tick(0.1 * (2.0 * std::f32::consts::PI * frequency * n as f32 / 48000.0).sin());
+//!         if n > 24000 { amplitude = amplitude.max(out_frame.abs()); } // steady state
+//!     }
+//!     amplitude
+//! }
+//! for oversampling in [false, true] {
+//!     let mut ladder1 = LadderFilter::new(48000, 500.0, 0.0, 1.0);
+//!     ladder1.set_oversampling(oversampling);
+//!     let pass = amplitude(&mut ladder1, 100.0);
+//!     let stop = amplitude(&mut ladder1, 4000.0);
+//!     debug_assert!(pass > 0.07 && pass < 0.1);
+//!     debug_assert!(20.0 * (stop / 0.1).log10() < -60.0); // about -69dB
+//! }
+//! ```
+//! Self-oscillation: with `resonance` = 1.0, an impulse starts a bounded sinusoid near the cutoff frequency:
+//! ```rust
+//! # use mydsp_jack::ladder::LadderFilter;
+//! # use mydsp_jack::AudioComponent;
+//! for (cutoff, oversampling) in [(1000.0, false), (5000.0, false), (5000.0, true)] {
+//!     let mut ladder2 = LadderFilter::new(48000, cutoff, 1.0, 1.0);
+//!     ladder2.set_oversampling(oversampling);
+//!     ladder2.tick(1.0);
+//!     let output: Vec<f32> = (0..48000).map(|_| ladder2.tick(0.0)).collect();
+//!     let last = &output[24000..]; // 0.5s
+//!     let peak = last.iter().fold(0.0_f32, |max, x| max.max(x.abs()));
+//!     let crossings = last.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count();
+//!     debug_assert!(peak > 0.05 && peak <= 1.0);
+//!     debug_assert!((crossings as f32 * 2.0 / cutoff - 1.0).abs() < 0.05); // frequency within 5%
+//! }
+//! ```
+use std::f32::consts::PI;
+use crate::AudioComponent;
+use crate::distortion::soft_clip;
+
+#[derive(Debug)]
+pub struct LadderFilter {
+    sample_rate: usize,
+    cutoff: f32,
+    resonance: f32, // [0.0, 1.0]
+    drive: f32,
+    oversampling: bool,
+    g: f32, // G = g / (1 + g) of the TPT one-poles, g = tan(pi * cutoff / internal rate).
+    stages: [f32; 4],
+    previous_input: f32, // for the interpolation of the oversampled input.
+}
+
+impl LadderFilter {
+    /// Returns a `LadderFilter` instance with a `cutoff` frequency (Hz), a `resonance` in [0.0, 1.0]
+    /// (1.0 -> self-oscillation) and a positive `drive` (gain before the saturation, 1.0 -> clean at low levels).
+    /// Oversampling is disabled.
+    ///
+    /// # Panics
+    /// The function panics if `resonance` is not in the range [0.0, 1.0] or if `drive` is not positive
+    /// (NaN included), the setters too.
+    /// ```rust, should_panic
+    /// # use mydsp_jack::ladder::LadderFilter;
+    /// let ladder_panic = LadderFilter::new(48000, 1000.0, 1.5, 1.0);
+    /// ```
+    /// ```rust, should_panic
+    /// # use mydsp_jack::ladder::LadderFilter;
+    /// let mut ladder_nan = LadderFilter::new(48000, 1000.0, 0.5, 1.0);
+    /// ladder_nan.set_drive(f32::NAN);
+    /// ```
+    pub fn new(sample_rate: usize, cutoff: f32, resonance: f32, drive: f32) -> Self {
+        let mut ladder = LadderFilter {
+            sample_rate,
+            cutoff: 0.0,
+            resonance: 0.0,
+            drive: 0.0,
+            oversampling: false,
+            g: 0.0,
+            stages: [0.0; 4],
+            previous_input: 0.0,
+        };
+        ladder.set_cutoff(cutoff);
+        ladder.set_resonance(resonance);
+        ladder.set_drive(drive);
+        ladder
+    }
+    /// Modifies the cutoff frequency (Hz), clamped to [1, 0.45 * sample_rate] so that it can be modulated per sample.
+    pub fn set_cutoff(&mut self, cutoff: f32) {
+        self.cutoff = cutoff.clamp(1.0, 0.45 * self.sample_rate as f32);
+        self.update_coefficient();
+    }
+    /// Modifies the resonance in [0.0, 1.0].
+    pub fn set_resonance(&mut self, resonance: f32) {
+        if !(0.0..=1.0).contains(&resonance) {
+            panic!("!!!LadderFilter panic: resonance must be in the range [0.0, 1.0]");
+        }
+        self.resonance = resonance;
+    }
+    /// Modifies the drive (gain before the saturation).
+    pub fn set_drive(&mut self, drive: f32) {
+        if drive.is_nan() || drive <= 0.0 {
+            panic!("!!!LadderFilter panic: drive must be positive");
+        }
+        self.drive = drive;
+    }
+    /// Enables or disables the 2x oversampling.
+    pub fn set_oversampling(&mut self, oversampling: bool) {
+        self.oversampling = oversampling;
+        self.update_coefficient();
+    }
+    /// Returns the cutoff frequency (Hz), after clamping.
+    pub fn cutoff(&self) -> f32 {
+        self.cutoff
+    }
+    /// Clears the state of the stages (not the parameters).
+    pub fn reset(&mut self) {
+        self.stages = [0.0; 4];
+        self.previous_input = 0.0;
+    }
+    fn update_coefficient(&mut self) {
+        let internal_rate = self.sample_rate as f32 * if self.oversampling { 2.0 } else { 1.0 };
+        let g = (PI * self.cutoff / internal_rate).tan();
+        self.g = g / (1.0 + g);
+    }
+    /// Processes one frame at the internal rate.
+    fn tick_ladder(&mut self, in_frame: f32) -> f32 {
+        // the feedback is slightly above the linear threshold (4) at resonance 1.0,
+        // so that the oscillation builds up and is limited by the saturation.
+        let feedback = 4.2 * self.resonance;
+        // linear solve of the loop: output = G^4 * input + sum of G^(3 - i) * (1 - G) * stages[i].
+        let (g, g2) = (self.g, self.g * self.g);
+        let state_sum = (1.0 - g) * (g * g2 * self.stages[0] + g2 * self.stages[1] + g * self.stages[2] + self.stages[3]);
+        let estimated_output = (g2 * g2 * in_frame + state_sum) / (1.0 + feedback * g2 * g2);
+        let mut stage_input = soft_clip(in_frame - feedback * estimated_output);
+        let mut output = 0.0;
+        for stage in self.stages.iter_mut() {
+            let v = (stage_input - *stage) * g;
+            output = v + *stage;
+            *stage = output + v;
+            stage_input = soft_clip(output);
+        }
+        output
+    }
+}
+
+impl AudioComponent for LadderFilter {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        let in_frame = in_frame * self.drive;
+        if self.oversampling {
+            // linear interpolation up, average of the two frames down.
+            let first = self.tick_ladder(0.5 * (self.previous_input + in_frame));
+            let second = self.tick_ladder(in_frame);
+            self.previous_input = in_frame;
+            0.5 * (first + second)
+        } else {
+            self.tick_ladder(in_frame)
+        }
+    }
+}
@@ -18,6 +18,11 @@
  * it can only be implemented in the user's binary crate by defining a new struct `Patch`. (**orphan rule**) 
  * So the final result is that `mydsp-jack` is completely independent of `jack-rust`.
  * 
+ * ## Optional `jack` cargo feature
+ * With `features = ["jack"]`, the generic `jack_runner::JackRunner` implements `ProcessHandler` 
+ * for any `AudioComponent` (including `graph::Graph`), so `Patch` does not need to be written anymore. 
+ * The default build stays independent of `jack-rust`.
+ * 
  * ## #[derive(Debug)]
  * Structs implant `#[derive(Debug)]`, to use `std::fmt formatting` traits, derive from Debug trait.
  * 
@@ -26,10 +31,10 @@
  * # TODO:
  * 1. finir + doc + autotest
  * 2. test
- * 2.5. jack block connect with code
- * 3. graph
- * 4. <https://github.com/orottier/web-audio-api-rs>
- * 5. <https://github.com/RustAudio/dasp>  
+ *    2.5. jack block connect with code
+ * 3. <https://github.com/orottier/web-audio-api-rs>
+ * 4. <https://github.com/RustAudio/dasp>  
+ * 
  * Parameters of f32 type take integers `i32` or `u32` as well. (use enum() instead?) (more info in echo.rs)
 */
 
@@ -44,31 +49,138 @@
 
 pub mod dummy;
 pub mod gain;
+#[doc(alias = "envelope")]
+pub mod adsr;
 #[doc(alias = "PWM")]
 pub mod pwm;
 pub mod noise;
 #[doc(alias = "sinetable")]
 #[doc(alias = "table")]
 pub mod sine_table;
+#[doc(alias = "wavetable_osc")]
+pub mod wavetable;
 #[doc(alias = "phase")]
 pub mod phasor;
+#[doc(alias = "bpm")]
+#[doc(alias = "note_value")]
+pub mod tempo;
+#[doc(alias = "transport")]
+#[doc(alias = "sync")]
+pub mod clock;
 #[doc(alias = "sinewave")]
 pub mod sine;
+#[doc(alias = "saw")]
+#[doc(alias = "square")]
+#[doc(alias = "triangle")]
+#[doc(alias = "pulse")]
+pub mod polyblep;
 pub mod delay;
 pub mod echo;
+#[doc(alias = "pingpong")]
+pub mod ping_pong;
+#[doc(alias = "feedback_comb")]
+#[doc(alias = "feedforward_comb")]
+pub mod comb;
+#[doc(alias = "schroeder")]
+pub mod allpass;
+#[doc(alias = "freeverb")]
+pub mod reverb;
+#[doc(alias = "feedback_delay_network")]
+pub mod fdn;
 #[doc(alias = "varible_delay")]
 #[doc(alias = "delay_varible")]
 pub mod delay_var;
+#[doc(alias = "multi_tap")]
+#[doc(alias = "tap")]
+pub mod multitap;
 pub mod smooth;
+#[doc(alias = "follower")]
+#[doc(alias = "rms")]
+#[doc(alias = "meter")]
+pub mod envelope_follower;
 pub mod one_zero;
+#[doc(alias = "filter")]
+#[doc(alias = "eq")]
+pub mod biquad;
+#[doc(alias = "state_variable_filter")]
+pub mod svf;
+#[doc(alias = "moog")]
+pub mod ladder;
 pub mod distortion;
 pub mod am;
 pub mod fm;
-// pub mod flanger;
-// pub mod ks;
+#[doc(alias = "stereo")]
+#[doc(alias = "multichannel")]
+pub mod multi;
+#[doc(alias = "patch")]
+pub mod graph;
+#[cfg(feature = "jack")]
+pub mod jack_runner;
+pub mod flanger;
+#[doc(alias = "ensemble")]
+pub mod chorus;
+pub mod phaser;
+pub mod ks;
 
+/// Common interface of all the mono components of `mydsp-jack`.
 pub trait AudioComponent: Send + Sync{ 
+    /// Processes one frame and returns the output frame.
     fn tick(&mut self, in_frame: f32) -> f32;
+    /// Processes a whole block of frames (e.g. a JACK period) in one call.
+    /// 
+    /// The default implementation calls `tick()` on each frame, 
+    /// components override it when a block can be computed more efficiently (auto-vectorization).
+    /// 
+    /// # Examples:
+    /// Processing a block gives the same result as calling `tick()` on each frame:
+    /// ```rust
+    /// # use mydsp_jack::delay::Delay;
+    /// # use mydsp_jack::AudioComponent;
+    /// let input = [1.0, 2.0, 3.0, 4.0, 5.0];
+    /// let mut output = [0.0; 5];
+    /// let mut delay1 = Delay::new(2);
+    /// delay1.process_block(&input, &mut output);
+    /// debug_assert_eq!(output, [0.0, 0.0, 1.0, 2.0, 3.0]);
+    /// # let mut delay2 = Delay::new(2);
+    /// # let ticked: Vec<f32> = input.iter().map(|x| delay2.tick(*x)).collect();
+    /// # debug_assert_eq!(output.to_vec(), ticked);
+    /// # use mydsp_jack::{echo::Echo, distortion::Distortion, gain::Gain, phasor::Phasor, sine::SineWave, sine_table::SineTable};
+    /// # static SINETABLE1: once_cell::sync::Lazy<SineTable> = once_cell::sync::Lazy::new(|| SineTable::new(1024));
+    /// # fn same<C: AudioComponent>(mut block: C, mut ticked: C) {
+    /// #     let input: Vec<f32> = (0..300).map(|i| (i as f32 * 0.1).sin()).collect();
+    /// #     let mut output = vec![0.0; input.len()];
+    /// #     for (i, o) in input.chunks(64).zip(output.chunks_mut(64)) { block.process_block(i, o); }
+    /// #     let expected: Vec<f32> = input.iter().map(|x| ticked.tick(*x)).collect();
+    /// #     debug_assert_eq!(output, expected);
+    /// # }
+    /// # same(Echo::new(50, 0.6, 0.7, None), Echo::new(50, 0.6, 0.7, None));
+    /// # same(Delay::new(100), Delay::new(100));
+    /// # same(Distortion::new(0.3, 0.1, 0.8), Distortion::new(0.3, 0.1, 0.8));
+    /// # same(Gain::new(0.5), Gain::new(0.5));
+    /// # same(Phasor::new(0.3, 0.01), Phasor::new(0.3, 0.01));
+    /// # same(SineWave::new(&SINETABLE1, Phasor::new(None, 0.01)), SineWave::new(&SINETABLE1, Phasor::new(None, 0.01)));
+    /// ```
+    /// # Panics
+    /// The function panics if `input` and `output` do not have the same length.
+    /// ```rust, should_panic
+    /// # use mydsp_jack::dummy::Dummy;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut dummy = Dummy::new();
+    /// dummy.process_block(&[0.0; 4], &mut [0.0; 3]);
+    /// ```
+    fn process_block(&mut self, input: &[f32], output: &mut [f32]) {
+        check_block(input, output);
+        for (in_frame, out_frame) in input.iter().zip(output.iter_mut()) {
+            *out_frame = self.tick(*in_frame);
+        }
+    }
+}
+
+/// Panics if `input` and `output` blocks do not have the same length, used by `process_block()`.
+pub(crate) fn check_block<T>(input: &[T], output: &[T]) {
+    if input.len() != output.len() {
+        panic!("!!!Panic: input and output blocks must have the same length");
+    }
 }
 
 pub fn multiply(signals: &[f32]) -> f32 {
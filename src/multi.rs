@@ -0,0 +1,140 @@
+//! The `MultiAudioComponent` trait processes frames of `N` channels (`[f32; N]`),
+//! and the `MultiChannel` adapter lifts any mono `AudioComponent` into `N` independent channels.
+//!
+//! Stereo versions of the existing effects are just `MultiChannel` instances with 2 channels,
+//! e.g. `StereoEcho` = `Stereo<Echo>` = `MultiChannel<Echo, 2>`,
+//! the parameters are given once and shared by both channels so they stay in sync.
+//!
+//! **memo**: the method is named `tick_frame` (not `tick`) so that a type can implement both traits
+//! without ambiguity at call sites.
+use crate::AudioComponent;
+use crate::echo::Echo;
+use crate::distortion::Distortion;
+use crate::smooth::Smooth;
+
+/// A frame of `N` channels, e.g. `Frame<2>` = `[left, right]`.
+pub type Frame<const N: usize> = [f32; N];
+
+/// Common interface of the components processing `N` channels at once.
+pub trait MultiAudioComponent<const N: usize>: Send + Sync {
+    /// Processes one frame of `N` channels and returns the output frame.
+    fn tick_frame(&mut self, in_frame: Frame<N>) -> Frame<N>;
+    /// Processes a whole block of frames in one call, the default implementation calls `tick_frame()` on each frame.
+    ///
+    /// # Panics
+    /// The function panics if `input` and `output` do not have the same length.
+    fn process_block_frames(&mut self, input: &[Frame<N>], output: &mut [Frame<N>]) {
+        crate::check_block(input, output);
+        for (in_frame, out_frame) in input.iter().zip(output.iter_mut()) {
+            *out_frame = self.tick_frame(*in_frame);
+        }
+    }
+}
+
+/// `N` independent instances of a mono `AudioComponent`, one per channel.
+#[derive(Debug)]
+pub struct MultiChannel<C: AudioComponent, const N: usize> {
+    channels: [C; N],
+}
+
+/// Two independent channels: `[left, right]`.
+pub type Stereo<C> = MultiChannel<C, 2>;
+/// Stereo `Echo`.
+pub type StereoEcho = Stereo<Echo>;
+/// Stereo `Distortion`.
+pub type StereoDistortion = Stereo<Distortion>;
+/// Stereo `Smooth`.
+pub type StereoSmooth = Stereo<Smooth>;
+
+impl<C: AudioComponent, const N: usize> MultiChannel<C, N> {
+    /// Returns a `MultiChannel` instance from an array of already created components (one per channel).
+    ///
+    /// # Examples:
+    /// Creating a stereo `Delay` with a different delay for each channel:
+    /// ```rust
+    /// # use mydsp_jack::delay::Delay;
+    /// # use mydsp_jack::multi::{MultiAudioComponent, Stereo};
+    /// let mut delay1 = Stereo::new([Delay::new(1), Delay::new(2)]);
+    /// debug_assert_eq!(delay1.tick_frame([1.0, 1.0]), [0.0, 0.0]);
+    /// debug_assert_eq!(delay1.tick_frame([2.0, 2.0]), [1.0, 0.0]);
+    /// debug_assert_eq!(delay1.tick_frame([3.0, 3.0]), [2.0, 1.0]);
+    /// ```
+    pub fn new(channels: [C; N]) -> Self {
+        MultiChannel { channels }
+    }
+    /// Returns a `MultiChannel` instance by calling `new_channel` for each channel index.
+    ///
+    /// # Examples:
+    /// Creating a `StereoEcho` with the same parameters for both channels:
+    /// ```rust
+    /// # use mydsp_jack::echo::Echo;
+    /// # use mydsp_jack::multi::{MultiAudioComponent, StereoEcho};
+    /// let nframes_delay: usize = 48000 / 2; // delay of 0.5s if sample rate = 48000Hz
+    /// let mut echo1 = StereoEcho::from_fn(|_| Echo::new(nframes_delay, 0.6, 0.7, None));
+    /// let out_frame = echo1.tick_frame([1.0, -1.0]);
+    /// # debug_assert_eq!(out_frame, [0.7, -0.7]);
+    /// ```
+    pub fn from_fn(new_channel: impl FnMut(usize) -> C) -> Self {
+        MultiChannel { channels: std::array::from_fn(new_channel) }
+    }
+    /// Returns the number of channels.
+    pub fn channels(&self) -> usize {
+        N
+    }
+    /// Returns a reference to the component of channel `index`.
+    pub fn channel(&self, index: usize) -> &C {
+        &self.channels[index]
+    }
+    /// Returns a mutable reference to the component of channel `index`.
+    pub fn channel_mut(&mut self, index: usize) -> &mut C {
+        &mut self.channels[index]
+    }
+    /// Calls `f` on the component of each channel, to modify all the channels in sync.
+    ///
+    /// # Examples:
+    /// Modifying the gain of both channels:
+    /// ```rust
+    /// # use mydsp_jack::gain::Gain;
+    /// # use mydsp_jack::multi::{MultiAudioComponent, Stereo};
+    /// let mut gain1 = Stereo::from_fn(|_| Gain::new(0.5));
+    /// gain1.for_each_channel(|gain| gain.set_gain(2.0));
+    /// debug_assert_eq!(gain1.tick_frame([0.0, 0.0]), [2.0, 2.0]);
+    /// ```
+    pub fn for_each_channel(&mut self, f: impl FnMut(&mut C)) {
+        self.channels.iter_mut().for_each(f);
+    }
+}
+
+impl<C: AudioComponent, const N: usize> MultiAudioComponent<N> for MultiChannel<C, N> {
+    fn tick_frame(&mut self, in_frame: Frame<N>) -> Frame<N> {
+        std::array::from_fn(|i| self.channels[i].tick(in_frame[i]))
+    }
+}
+
+/// Duplicates a mono frame into `N` channels.
+pub fn to_frame<const N: usize>(in_frame: f32) -> Frame<N> {
+    [in_frame; N]
+}
+
+/// Mixes down a frame of `N` channels into a mono frame (average of the channels).
+pub fn to_mono<const N: usize>(in_frame: Frame<N>) -> f32 {
+    crate::add(&in_frame) / N as f32
+}
+
+/// Returns the constant power gains `[left, right]` of `pan` in [-1.0, 1.0] (-1.0 -> left, 0.0 -> -3dB each, 1.0 -> right).
+///
+/// # Examples:
+/// ```rust
+/// # use mydsp_jack::multi::pan_gains;
+/// let [left, right] = pan_gains(0.0);
+/// debug_assert!((left - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6 && (left * left + right * right - 1.0).abs() < 1e-6);
+/// ```
+/// # Panics
+/// The function panics if `pan` is not in the range [-1.0, 1.0].
+pub fn pan_gains(pan: f32) -> Frame<2> {
+    if !(-1.0..=1.0).contains(&pan) {
+        panic!("!!!Panic: pan must be in the range [-1.0, 1.0]");
+    }
+    let angle = (pan + 1.0) * std::f32::consts::FRAC_PI_4; // [0, pi / 2]
+    [angle.cos(), angle.sin()]
+}
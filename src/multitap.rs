@@ -0,0 +1,166 @@
+//! The `MultiTapDelay` component reads several taps from one circular buffer (`DelayVar::read_at()`):
+//! rhythmic delays, early reflections...
+//!
+//! Each tap has its own delay time (seconds, fractional), gain and pan in [-1.0, 1.0] (-1.0 -> left, 1.0 -> right,
+//! constant power). Taps can be added and removed while running, up to the capacity given to `new()`
+//! (no allocation after the creation).
+//!
+//! `tick()` returns the mono mix (sum of the taps * gains, without pan), `tick_stereo()` the panned mix,
+//! and the output of each tap of the last frame is available with `tap_output()` or `tap_outputs()`.
+//!
+//! # Examples:
+//! Impulse response of two taps at 1ms and 2.5ms at 1kHz (1 and 2.5 frames), the second one panned hard right:
+//! ```rust
+//! # use mydsp_jack::multitap::MultiTapDelay;
+//! let mut multitap1 = MultiTapDelay::new(1000, 0.01, 4);
+//! let tap1 = multitap1.add_tap(0.001, 1.0, 0.0);
+//! let tap2 = multitap1.add_tap(0.0025, 0.5, 1.0);
+//! let response: Vec<[f32; 2]> = (0..4).map(|n| multitap1.tick_stereo(if n == 0 { 1.0 } else { 0.0 })).collect();
+//! debug_assert_eq!(response[0], [0.0, 0.0]);
+//! debug_assert!(response[1].iter().all(|x| (x - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6)); // centered, -3dB
+//! debug_assert!(response[2][0].abs() < 1e-6 && (response[2][1] - 0.25).abs() < 1e-6); // half of the second tap
+//! debug_assert!(response[3][0].abs() < 1e-6 && (response[3][1] - 0.25).abs() < 1e-6);
+//! debug_assert_eq!(multitap1.tap_output(tap1), Some(0.0));
+//! debug_assert_eq!(multitap1.tap_output(tap2), Some(0.25));
+//! multitap1.remove_tap(tap1);
+//! debug_assert_eq!(multitap1.tap_output(tap1), None);
+//! debug_assert_eq!(multitap1.taps(), 1);
+//! ```
+use crate::AudioComponent;
+use crate::delay_var::DelayVar;
+use crate::multi::{Frame, MultiAudioComponent, pan_gains};
+
+/// Identifies a tap of a `MultiTapDelay`, returned when adding a tap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TapId(usize);
+
+#[derive(Debug)]
+struct Tap {
+    id: TapId,
+    delay: f32, // frames
+    gain: f32,
+    pan_gains: [f32; 2], // constant power [left, right].
+    output: f32, // last output of the tap (with gain, without pan).
+}
+
+#[derive(Debug)]
+pub struct MultiTapDelay {
+    delay: DelayVar,
+    taps: Vec<Tap>, // capacity allocated in `new()`.
+    next_id: usize,
+    sample_rate: usize,
+    max_delay_time: f32,
+}
+
+impl MultiTapDelay {
+    /// Returns a `MultiTapDelay` instance without taps, with taps up to `max_delay_time` seconds
+    /// and room for `max_taps` taps.
+    pub fn new(sample_rate: usize, max_delay_time: f32, max_taps: usize) -> Self {
+        MultiTapDelay {
+            delay: DelayVar::with_max_delay_time(sample_rate, max_delay_time),
+            taps: Vec::with_capacity(max_taps),
+            next_id: 0,
+            sample_rate,
+            max_delay_time,
+        }
+    }
+    /// Adds a tap of `delay_time` (seconds), `gain` and `pan` in [-1.0, 1.0], returns its id.
+    ///
+    /// # Panics
+    /// The function panics if there is no room for a new tap, if `delay_time` is not in the range [0, max_delay_time]
+    /// or if `pan` is not in the range [-1.0, 1.0].
+    /// ```rust, should_panic
+    /// # use mydsp_jack::multitap::MultiTapDelay;
+    /// let mut multitap_panic = MultiTapDelay::new(48000, 1.0, 1);
+    /// multitap_panic.add_tap(0.25, 0.5, 0.0);
+    /// multitap_panic.add_tap(0.5, 0.5, 0.0); // panic
+    /// ```
+    pub fn add_tap(&mut self, delay_time: f32, gain: f32, pan: f32) -> TapId {
+        if self.taps.len() == self.taps.capacity() {
+            panic!("!!!MultiTapDelay panic: no room for a new tap (max_taps = {})", self.taps.capacity());
+        }
+        let tap = Tap {
+            id: TapId(self.next_id),
+            delay: self.delay_frames(delay_time),
+            gain,
+            pan_gains: pan_gains(pan),
+            output: 0.0,
+        };
+        self.next_id += 1;
+        self.taps.push(tap);
+        TapId(self.next_id - 1)
+    }
+    /// Removes a tap, returns `false` if it was already removed.
+    pub fn remove_tap(&mut self, tap: TapId) -> bool {
+        let len = self.taps.len();
+        self.taps.retain(|t| t.id != tap);
+        self.taps.len() != len
+    }
+    /// Removes all the taps.
+    pub fn clear_taps(&mut self) {
+        self.taps.clear();
+    }
+    /// Returns the number of taps.
+    pub fn taps(&self) -> usize {
+        self.taps.len()
+    }
+    /// Modifies the delay time (seconds) of a tap.
+    pub fn set_tap_time(&mut self, tap: TapId, delay_time: f32) {
+        let delay = self.delay_frames(delay_time);
+        self.tap_mut(tap).delay = delay;
+    }
+    /// Modifies the gain of a tap.
+    pub fn set_tap_gain(&mut self, tap: TapId, gain: f32) {
+        self.tap_mut(tap).gain = gain;
+    }
+    /// Modifies the pan of a tap in [-1.0, 1.0].
+    pub fn set_tap_pan(&mut self, tap: TapId, pan: f32) {
+        self.tap_mut(tap).pan_gains = pan_gains(pan);
+    }
+    /// Returns the output of a tap for the last frame (with gain, without pan), `None` if it was removed.
+    pub fn tap_output(&self, tap: TapId) -> Option<f32> {
+        self.taps.iter().find(|t| t.id == tap).map(|t| t.output)
+    }
+    /// Returns the outputs of all the taps for the last frame (with gain, without pan).
+    pub fn tap_outputs(&self) -> impl Iterator<Item = (TapId, f32)> + '_ {
+        self.taps.iter().map(|t| (t.id, t.output))
+    }
+    /// Processes one frame and returns the stereo mix of the taps `[left, right]`.
+    pub fn tick_stereo(&mut self, in_frame: f32) -> Frame<2> {
+        self.delay.tick(in_frame);
+        let mut output = [0.0; 2];
+        for tap in self.taps.iter_mut() {
+            tap.output = tap.gain * self.delay.read_at(tap.delay);
+            output[0] += tap.pan_gains[0] * tap.output;
+            output[1] += tap.pan_gains[1] * tap.output;
+        }
+        output
+    }
+    fn delay_frames(&self, delay_time: f32) -> f32 {
+        if !(0.0..=self.max_delay_time).contains(&delay_time) {
+            panic!("!!!MultiTapDelay panic: delay_time must be in the range [0, {}]", self.max_delay_time);
+        }
+        delay_time * self.sample_rate as f32
+    }
+    fn tap_mut(&mut self, tap: TapId) -> &mut Tap {
+        self.taps.iter_mut().find(|t| t.id == tap).unwrap_or_else(|| panic!("!!!MultiTapDelay panic: unknown tap {:?}", tap))
+    }
+}
+
+impl AudioComponent for MultiTapDelay {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        self.delay.tick(in_frame);
+        let mut output = 0.0;
+        for tap in self.taps.iter_mut() {
+            tap.output = tap.gain * self.delay.read_at(tap.delay);
+            output += tap.output;
+        }
+        output
+    }
+}
+
+impl MultiAudioComponent<2> for MultiTapDelay {
+    fn tick_frame(&mut self, in_frame: Frame<2>) -> Frame<2> {
+        self.tick_stereo(0.5 * (in_frame[0] + in_frame[1]))
+    }
+}
@@ -1,23 +1,64 @@
-//! The `Noise` component generates white noise.
-//! 
+//! The noise components generate white, pink, brown (red), velvet and Gaussian noise.
+//!
+//! All of them own a seedable pseudo-random number generator (`SmallRng`, Xoshiro256++ on 64-bit platforms):
+//! `tick()` does not allocate nor touch thread-local state, so they are safe in the audio callback,
+//! and `with_seed()` makes them reproducible (e.g. in tests).
+//! `new()` seeds the generator from the OS once, when the instance is created.
+//!
 //! **memo**:
-//! `Uniform::new` and `Uniform::new_inclusive` construct a uniform distribution sampling from the given range; 
-//!     these functions may do extra work up front to make sampling of multiple values faster.  
+//! `Uniform::new` and `Uniform::new_inclusive` construct a uniform distribution sampling from the given range;
+//!     these functions may do extra work up front to make sampling of multiple values faster.
 //! <https://docs.rs/rand/0.6.5/rand/distributions/uniform/struct.Uniform.html>
-//! ## TODO:
-//! * Add other types of noise.
+//!
+//! # Spectral slopes
+//! Mean power of the bins [750Hz, 1500Hz) compared with [3000Hz, 6000Hz) (2 octaves higher) at 48kHz:
+//! white -> 0dB, pink -> 2 * (-3dB), brown -> 2 * (-6dB).
+//! ```rust
+//! # use mydsp_jack::noise::{WhiteNoise, PinkNoise, BrownNoise, VelvetNoise};
+//! # use mydsp_jack::AudioComponent;
+//! /// Mean power of the DFT bins [from, to) of segments of 1024 frames.
+//! fn band_power(signal: &[f32], from: usize, to: usize) -> f64 {
+//!     let mut power = 0.0;
+//!     for segment in signal.chunks(1024) {
+//!         for bin in from..to {
+//!             let (mut re, mut im) = (0.0_f64, 0.0_f64);
+//!             for (i, x) in segment.iter().enumerate() {
+//!                 let w = 2.0 * std::f64::consts::PI * ((bin * i) % 1024) as f64 / 1024.0;
+//!                 re += *x as f64 * w.cos();
+//!                 im -= *x as f64 * w.sin();
+//!             }
+//!             power += re * re + im * im;
+//!         }
+//!     }
+//!     power / (to - from) as f64
+//! }
+//! fn slope_db(noise: &mut impl AudioComponent) -> f64 {
+//!     let signal: Vec<f32> = (0..16 * 1024).map(|_| noise.tick(0.0)).collect();
+//!     10.0 * (band_power(&signal, 64, 128) / band_power(&signal, 16, 32)).log10()
+//! }
+//! let white = slope_db(&mut WhiteNoise::with_seed(1));
+//! let pink = slope_db(&mut PinkNoise::with_seed(2));
+//! let brown = slope_db(&mut BrownNoise::with_seed(3));
+//! let velvet = slope_db(&mut VelvetNoise::with_seed(48000, 2000.0, 4));
+//! debug_assert!(white.abs() < 1.5);
+//! debug_assert!((pink + 6.0).abs() < 1.5);
+//! debug_assert!((brown + 12.0).abs() < 1.5);
+//! debug_assert!(velvet.abs() < 1.5);
+//! ```
 use crate::AudioComponent;
-// use rand::Rng;
 use rand::distributions::{Distribution, Uniform};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
 
 #[derive(Debug)]
 pub struct WhiteNoise {
+    rng: SmallRng,
     between: Uniform<f32>,
 }
 
 impl WhiteNoise {
     /// Creating a `WhiteNoise` instance to generate white noise.
-    /// 
+    ///
     /// # Examples:
     /// Creating a `Noise` instance without any input:
     /// ```rust
@@ -25,17 +66,231 @@ impl WhiteNoise {
     /// let wn1 = WhiteNoise::new();
     /// ```
     pub fn new() -> WhiteNoise {
+        WhiteNoise::from_rng(SmallRng::from_entropy())
+    }
+    /// Creating a `WhiteNoise` instance whose sequence is reproducible for a given `seed`.
+    ///
+    /// # Examples:
+    /// ```rust
+    /// # use mydsp_jack::noise::WhiteNoise;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut wn2 = WhiteNoise::with_seed(42);
+    /// let mut wn3 = WhiteNoise::with_seed(42);
+    /// debug_assert_eq!(wn2.tick(0.0), wn3.tick(0.0));
+    /// # for _ in 0..1000 { let x = wn2.tick(0.0); debug_assert!((-1.0..1.0).contains(&x)); debug_assert_eq!(x, wn3.tick(0.0)); }
+    /// ```
+    pub fn with_seed(seed: u64) -> WhiteNoise {
+        WhiteNoise::from_rng(SmallRng::seed_from_u64(seed))
+    }
+    fn from_rng(rng: SmallRng) -> WhiteNoise {
         WhiteNoise {
+            rng,
             between: Uniform::from(-1.0..1.0),
         }
     }
 }
 
+impl Default for WhiteNoise {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AudioComponent for WhiteNoise {
     fn tick(&mut self, _in_frame: f32) -> f32 {
-        // let mut rng = rand::thread_rng(); 
-        // self.gain * rng.gen::<f32>() * 2.0 - 1.0 // [-1.0, 1.0)
-        let mut rng = rand::thread_rng();
-        self.between.sample(&mut rng) // [-1.0, 1.0)
+        self.between.sample(&mut self.rng) // [-1.0, 1.0)
+    }
+}
+
+/// Pink noise (-3dB per octave), white noise filtered with Paul Kellet's refined method (approximately [-1.0, 1.0]).
+/// <https://www.firstpr.com.au/dsp/pink-noise/>
+#[derive(Debug)]
+pub struct PinkNoise {
+    white: WhiteNoise,
+    b: [f32; 7], // states of the filters.
+}
+
+impl PinkNoise {
+    /// Creating a `PinkNoise` instance to generate pink noise.
+    pub fn new() -> PinkNoise {
+        PinkNoise { white: WhiteNoise::new(), b: [0.0; 7] }
+    }
+    /// Creating a `PinkNoise` instance whose sequence is reproducible for a given `seed`.
+    pub fn with_seed(seed: u64) -> PinkNoise {
+        PinkNoise { white: WhiteNoise::with_seed(seed), b: [0.0; 7] }
+    }
+}
+
+impl Default for PinkNoise {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioComponent for PinkNoise {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        let white = self.white.tick(in_frame);
+        let b = &mut self.b;
+        b[0] = 0.99886 * b[0] + white * 0.0555179;
+        b[1] = 0.99332 * b[1] + white * 0.0750759;
+        b[2] = 0.96900 * b[2] + white * 0.153852;
+        b[3] = 0.86650 * b[3] + white * 0.3104856;
+        b[4] = 0.55000 * b[4] + white * 0.5329522;
+        b[5] = -0.7616 * b[5] - white * 0.0168980;
+        let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+        b[6] = white * 0.115926;
+        pink * 0.11 // gain compensation.
+    }
+}
+
+/// Brown (red) noise (-6dB per octave), white noise integrated by a leaky integrator (approximately [-1.0, 1.0]).
+#[derive(Debug)]
+pub struct BrownNoise {
+    white: WhiteNoise,
+    previous_output: f32,
+}
+
+impl BrownNoise {
+    /// Creating a `BrownNoise` instance to generate brown noise.
+    pub fn new() -> BrownNoise {
+        BrownNoise { white: WhiteNoise::new(), previous_output: 0.0 }
+    }
+    /// Creating a `BrownNoise` instance whose sequence is reproducible for a given `seed`.
+    pub fn with_seed(seed: u64) -> BrownNoise {
+        BrownNoise { white: WhiteNoise::with_seed(seed), previous_output: 0.0 }
+    }
+}
+
+impl Default for BrownNoise {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioComponent for BrownNoise {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        let white = self.white.tick(in_frame);
+        self.previous_output = (self.previous_output + 0.02 * white) / 1.02; // leaky: no DC drift.
+        self.previous_output * 3.5 // gain compensation.
+    }
+}
+
+/// Velvet noise: one impulse of random sign (1.0 or -1.0) at a random position in each period of `sample_rate / density` frames,
+/// 0.0 elsewhere (sparse, spectrally white, used for reverbs and decorrelation).
+#[derive(Debug)]
+pub struct VelvetNoise {
+    rng: SmallRng,
+    period: f32, // frames between two impulses in average.
+    current_frame: usize, // position in the current period.
+    period_frames: usize, // length of the current period (frames).
+    impulse_frame: usize, // position of the impulse in the current period.
+    impulse: f32, // 1.0 or -1.0
+    remainder: f32, // fractional part of the periods.
+}
+
+impl VelvetNoise {
+    /// Creating a `VelvetNoise` instance with a `density` of impulses per second.
+    ///
+    /// # Examples:
+    /// ```rust
+    /// # use mydsp_jack::noise::VelvetNoise;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut vn1 = VelvetNoise::with_seed(48000, 1000.0, 7);
+    /// let nb_impulses = (0..48000).filter(|_| vn1.tick(0.0) != 0.0).count();
+    /// debug_assert_eq!(nb_impulses, 1000); // 1s
+    /// ```
+    /// # Panics
+    /// The function panics if `density` is not in the range (0.0, sample_rate].
+    /// ```rust, should_panic
+    /// # use mydsp_jack::noise::VelvetNoise;
+    /// let vn_panic = VelvetNoise::new(48000, 0.0);
+    /// ```
+    pub fn new(sample_rate: usize, density: f32) -> VelvetNoise {
+        VelvetNoise::from_rng(SmallRng::from_entropy(), sample_rate, density)
+    }
+    /// Creating a `VelvetNoise` instance whose sequence is reproducible for a given `seed`.
+    pub fn with_seed(sample_rate: usize, density: f32, seed: u64) -> VelvetNoise {
+        VelvetNoise::from_rng(SmallRng::seed_from_u64(seed), sample_rate, density)
+    }
+    fn from_rng(rng: SmallRng, sample_rate: usize, density: f32) -> VelvetNoise {
+        if density <= 0.0 || density > sample_rate as f32 {
+            panic!("!!!Panic: density must be in the range (0, sample_rate]");
+        }
+        let mut velvet = VelvetNoise {
+            rng,
+            period: sample_rate as f32 / density,
+            current_frame: 0,
+            period_frames: 0,
+            impulse_frame: 0,
+            impulse: 0.0,
+            remainder: 0.0,
+        };
+        velvet.next_period();
+        velvet
+    }
+    /// Draws the length, the impulse position and the impulse sign of the next period.
+    fn next_period(&mut self) {
+        let period = self.period + self.remainder;
+        self.period_frames = (period as usize).max(1);
+        self.remainder = period - self.period_frames as f32;
+        self.current_frame = 0;
+        self.impulse_frame = self.rng.gen_range(0..self.period_frames);
+        self.impulse = if self.rng.gen::<bool>() { 1.0 } else { -1.0 };
+    }
+}
+
+impl AudioComponent for VelvetNoise {
+    fn tick(&mut self, _in_frame: f32) -> f32 {
+        let output = if self.current_frame == self.impulse_frame { self.impulse } else { 0.0 };
+        self.current_frame += 1;
+        if self.current_frame == self.period_frames {
+            self.next_period();
+        }
+        output
+    }
+}
+
+/// Gaussian (normal) white noise of mean 0.0 and a given standard deviation (Box-Muller transform), not bounded.
+#[derive(Debug)]
+pub struct GaussianNoise {
+    rng: SmallRng,
+    standard_deviation: f32,
+    spare: Option<f32>, // Box-Muller generates 2 values at once.
+}
+
+impl GaussianNoise {
+    /// Creating a `GaussianNoise` instance with a given `standard_deviation`.
+    ///
+    /// # Examples:
+    /// ```rust
+    /// # use mydsp_jack::noise::GaussianNoise;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut gn1 = GaussianNoise::with_seed(0.5, 3);
+    /// let frames: Vec<f32> = (0..100000).map(|_| gn1.tick(0.0)).collect();
+    /// let mean = frames.iter().sum::<f32>() / frames.len() as f32;
+    /// let variance = frames.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / frames.len() as f32;
+    /// debug_assert!(mean.abs() < 0.01);
+    /// debug_assert!((variance.sqrt() - 0.5).abs() < 0.01);
+    /// ```
+    pub fn new(standard_deviation: f32) -> GaussianNoise {
+        GaussianNoise { rng: SmallRng::from_entropy(), standard_deviation, spare: None }
+    }
+    /// Creating a `GaussianNoise` instance whose sequence is reproducible for a given `seed`.
+    pub fn with_seed(standard_deviation: f32, seed: u64) -> GaussianNoise {
+        GaussianNoise { rng: SmallRng::seed_from_u64(seed), standard_deviation, spare: None }
+    }
+}
+
+impl AudioComponent for GaussianNoise {
+    fn tick(&mut self, _in_frame: f32) -> f32 {
+        if let Some(spare) = self.spare.take() {
+            return spare * self.standard_deviation;
+        }
+        let u1: f32 = 1.0 - self.rng.gen::<f32>(); // (0, 1], avoids ln(0)
+        let u2: f32 = self.rng.gen();
+        let radius = (-2.0 * u1.ln()).sqrt();
+        let angle = 2.0 * std::f32::consts::PI * u2;
+        self.spare = Some(radius * angle.sin());
+        radius * angle.cos() * self.standard_deviation
     }
 }
@@ -0,0 +1,204 @@
+//! The `Phaser` component mixes the input with a copy sent through a chain of first-order allpass stages
+//! (4 to `MAX_STAGES`): the phase shift of the chain cancels the input at some frequencies (notches),
+//! and an LFO sweeps the break frequency of the stages (phase shift of -90 degrees) between
+//! `min_frequency` and `max_frequency` (exponentially), which moves the notches.
+//!
+//! Each stage is H(z) = (a + z^-1) / (1 + a * z^-1) with a = (tan(pi * f / sample_rate) - 1) / (tan(pi * f / sample_rate) + 1).
+//! With n stages and mix = 0.5, there are (n + 1) / 2 notches (rounded down), where the chain shifts the phase by -180, -540... degrees.
+//! chain input = input + feedback * last chain output (feedback in (-1.0, 1.0), sharper notches and peaks),
+//! output = (1 - mix) * input + mix * chain output.
+//!
+//! In stereo (`tick_stereo()`), the LFO of the right channel is ahead of the left one by `stereo_phase`
+//! (in cycles, default 0.25 -> 90 degrees), `tick()` returns the average of both channels.
+//!
+//! # Examples:
+//! With a fixed break frequency of 1kHz and 4 stages, the first notch is where each stage shifts the phase
+//! by -45 degrees (tan(pi * f / sample_rate) = tan(pi * 1000 / sample_rate) * tan(pi / 8), about 415Hz),
+//! and the chain is in phase with the input at 1kHz (4 * -90 degrees):
+//! ```rust
+//! # use mydsp_jack::phaser::Phaser;
+//! # use mydsp_jack::sine_table::SineTable;
+//! use std::f32::consts::PI;
+//! use once_cell::sync::Lazy;
+//! static SINETABLE1: Lazy<SineTable> = Lazy::new(|| SineTable::new(4096));
+//! fn amplitude(frequency: f32) -> f32 {
+//!     let mut phaser1 = Phaser::new(48000, &SINETABLE1, 4, 0.5, 1000.0, 1000.0, 0.0);
+//!     let mut amplitude = 0.0_f32;
+//!     for n in 0..48000 {
+//!         let [left, _] = phaser1.tick_stereo((2.0 * PI * frequency * n as f32 / 48000.0).sin());
+//!         if n > 24000 { amplitude = amplitude.max(left.abs()); }
+//!     }
+//!     amplitude
+//! }
+//! let notch = ((PI * 1000.0 / 48000.0).tan() * (PI / 8.0).tan()).atan() * 48000.0 / PI;
+//! debug_assert!(amplitude(notch) < 0.01);
+//! debug_assert!((amplitude(1000.0) - 1.0).abs() < 0.01);
+//! ```
+//! The LFO sweeps the notches, the right channel being a quarter of a cycle ahead:
+//! ```rust
+//! # use mydsp_jack::phaser::Phaser;
+//! # use mydsp_jack::sine_table::SineTable;
+//! use once_cell::sync::Lazy;
+//! static SINETABLE1: Lazy<SineTable> = Lazy::new(|| SineTable::new(4096));
+//! let mut phaser2 = Phaser::new(48000, &SINETABLE1, 8, 2.0, 200.0, 4000.0, 0.5);
+//! let input = |n: usize| (2.0 * std::f32::consts::PI * 1000.0 * n as f32 / 48000.0).sin();
+//! let (mut min, mut max) = (f32::MAX, 0.0_f32);
+//! for window in 0..100 { // 10ms windows, 2 LFO cycles
+//!     let mut energy = 0.0;
+//!     let mut difference = 0.0;
+//!     for n in window * 480..(window + 1) * 480 {
+//!         let [left, right] = phaser2.tick_stereo(input(n));
+//!         energy += left * left;
+//!         difference += (left - right).abs();
+//!     }
+//!     min = min.min(energy);
+//!     max = max.max(energy);
+//!     if window > 10 { debug_assert!(difference > 0.0); }
+//! }
+//! debug_assert!(min < 0.05 * max); // the notches sweep across 1kHz
+//! ```
+use std::f32::consts::PI;
+use crate::AudioComponent;
+use crate::multi::{Frame, MultiAudioComponent};
+use crate::phasor::Phasor;
+use crate::sine_table::SineTable;
+
+/// Maximum number of allpass stages.
+pub const MAX_STAGES: usize = 12;
+
+#[derive(Debug)]
+pub struct Phaser {
+    sine_table: &'static SineTable,
+    lfo_phasor: Phasor,
+    sample_rate: usize,
+    stages: usize,
+    states: [[f32; MAX_STAGES]; 2], // [left, right] states of the stages (transposed direct form II).
+    chain_outputs: Frame<2>, // last outputs of the chains, for feedback.
+    min_frequency: f32,
+    max_frequency: f32,
+    feedback: f32,
+    mix: f32,
+    stereo_phase: f32,
+}
+
+impl Phaser {
+    /// Returns a `Phaser` instance with `stages` allpass stages in [4, `MAX_STAGES`], an LFO of `rate` (Hz)
+    /// sweeping the break frequency between `min_frequency` and `max_frequency` (Hz), and a `feedback` in (-1.0, 1.0).
+    ///
+    /// Default: mix 0.5, stereo phase 0.25.
+    ///
+    /// # Panics
+    /// The function panics if a parameter is out of its range.
+    /// ```rust, should_panic
+    /// # use mydsp_jack::phaser::Phaser;
+    /// # use mydsp_jack::sine_table::SineTable;
+    /// use once_cell::sync::Lazy;
+    /// static SINETABLE1: Lazy<SineTable> = Lazy::new(|| SineTable::new(4096));
+    /// let phaser_panic = Phaser::new(48000, &SINETABLE1, 14, 0.5, 200.0, 4000.0, 0.5);
+    /// ```
+    pub fn new(sample_rate: usize, sine_table: &'static SineTable, stages: usize, rate: f32, min_frequency: f32, max_frequency: f32, feedback: f32) -> Self {
+        let mut phaser = Phaser {
+            sine_table,
+            lfo_phasor: Phasor::with_frequency(sample_rate, rate),
+            sample_rate,
+            stages: 4,
+            states: [[0.0; MAX_STAGES]; 2],
+            chain_outputs: [0.0; 2],
+            min_frequency: 0.0,
+            max_frequency: 0.0,
+            feedback: 0.0,
+            mix: 0.5,
+            stereo_phase: 0.25,
+        };
+        phaser.set_stages(stages);
+        phaser.set_frequency_range(min_frequency, max_frequency);
+        phaser.set_feedback(feedback);
+        phaser
+    }
+    /// Modifies the number of allpass stages in [4, `MAX_STAGES`].
+    pub fn set_stages(&mut self, stages: usize) {
+        if !(4..=MAX_STAGES).contains(&stages) {
+            panic!("!!!Phaser panic: stages must be in the range [4, {}]", MAX_STAGES);
+        }
+        self.stages = stages;
+    }
+    /// Returns the number of allpass stages.
+    pub fn stages(&self) -> usize {
+        self.stages
+    }
+    /// Modifies the LFO rate (Hz).
+    pub fn set_rate(&mut self, rate: f32) {
+        self.lfo_phasor.set_frequency(rate);
+    }
+    /// Modifies the range (Hz) of the sweep of the break frequency, 0 < `min_frequency` <= `max_frequency` < sample_rate / 2.
+    pub fn set_frequency_range(&mut self, min_frequency: f32, max_frequency: f32) {
+        if min_frequency <= 0.0 || min_frequency > max_frequency || max_frequency >= 0.5 * self.sample_rate as f32 {
+            panic!("!!!Phaser panic: frequencies must satisfy 0 < min_frequency <= max_frequency < sample_rate / 2");
+        }
+        self.min_frequency = min_frequency;
+        self.max_frequency = max_frequency;
+    }
+    /// Modifies the feedback in (-1.0, 1.0).
+    pub fn set_feedback(&mut self, feedback: f32) {
+        if feedback <= -1.0 || feedback >= 1.0 {
+            panic!("!!!Phaser panic: feedback must be in the range (-1.0, 1.0)");
+        }
+        self.feedback = feedback;
+    }
+    /// Modifies the dry/wet mix in [0.0, 1.0] (0.5 -> deepest notches).
+    pub fn set_mix(&mut self, mix: f32) {
+        if !(0.0..=1.0).contains(&mix) {
+            panic!("!!!Phaser panic: mix must be in the range [0.0, 1.0]");
+        }
+        self.mix = mix;
+    }
+    /// Modifies the phase offset (cycles) of the LFO of the right channel in [0.0, 1.0), 0.0 -> same sweep in both channels.
+    pub fn set_stereo_phase(&mut self, stereo_phase: f32) {
+        if !(0.0..1.0).contains(&stereo_phase) {
+            panic!("!!!Phaser panic: stereo_phase must be in the range [0.0, 1.0)");
+        }
+        self.stereo_phase = stereo_phase;
+    }
+    /// Clears the states of the stages and the feedback.
+    pub fn reset(&mut self) {
+        self.states = [[0.0; MAX_STAGES]; 2];
+        self.chain_outputs = [0.0; 2];
+    }
+    /// Processes one stereo frame and returns the stereo output.
+    fn process(&mut self, in_frame: Frame<2>) -> Frame<2> {
+        let phase = self.lfo_phasor.tick(0.0);
+        let mut output = [0.0; 2];
+        for channel in 0..2 {
+            let lfo = self.sine_table.get_value((phase + channel as f32 * self.stereo_phase).fract()) * 0.5 + 0.5; // [0, 1]
+            let frequency = self.min_frequency * (self.max_frequency / self.min_frequency).powf(lfo);
+            let t = (PI * frequency / self.sample_rate as f32).tan();
+            let a = (t - 1.0) / (t + 1.0);
+            let mut x = in_frame[channel] + self.feedback * self.chain_outputs[channel];
+            for state in self.states[channel][..self.stages].iter_mut() {
+                let y = a * x + *state;
+                *state = x - a * y;
+                x = y;
+            }
+            self.chain_outputs[channel] = x;
+            output[channel] = (1.0 - self.mix) * in_frame[channel] + self.mix * x;
+        }
+        output
+    }
+    /// Processes one mono frame and returns the stereo output `[left, right]`.
+    pub fn tick_stereo(&mut self, in_frame: f32) -> Frame<2> {
+        self.process([in_frame, in_frame])
+    }
+}
+
+impl AudioComponent for Phaser {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        let [left, right] = self.tick_stereo(in_frame);
+        0.5 * (left + right)
+    }
+}
+
+impl MultiAudioComponent<2> for Phaser {
+    fn tick_frame(&mut self, in_frame: Frame<2>) -> Frame<2> {
+        self.process(in_frame)
+    }
+}
@@ -1,10 +1,15 @@
 //! The `Phasor` component for generating a phase that increments between [0.0, 1.0), creating oscillators or modulators. 
+//! 
+//! A `Phasor` can be created from a raw phase increment (`new`) or from a frequency in Hz (`with_frequency`), 
+//! phase_increment = frequency / sample_rate, e.g. 440.0 / 48000.0 (not `(440/48000) as f32`, integer division = 0).
 use crate::AudioComponent;
 
 #[derive(Debug)]
 pub struct Phasor {
     phase: f32, // current phase
     phase_increment: f32, // per tick
+    initial_phase: f32, // phase after `reset()`
+    sample_rate: Option<usize>, // only known if created with `with_frequency()`
 }
 
 impl Phasor {
@@ -31,7 +36,7 @@ impl Phasor {
     /// ```
     pub fn new(initial_phase: impl Into<Option<f32>>, phase_increment: f32) -> Self {
         let initial_phase = initial_phase.into().unwrap_or(0.0);
-        if initial_phase < 0.0 || initial_phase >= 1.0 {
+        if !(0.0..1.0).contains(&initial_phase) {
             panic!("!!!Panic: initial_phase must be in the range [0, 1)");
         }
         if phase_increment == 0.0 {
@@ -39,9 +44,36 @@ impl Phasor {
         }
         Phasor { 
             phase: initial_phase, 
-            phase_increment 
+            phase_increment,
+            initial_phase,
+            sample_rate: None,
         }
     }
+    /// Returns a `Phasor` instance with an initial phase of 0.0 and a `frequency` in Hz for a given `sample_rate`.
+    /// 
+    /// # Examples:
+    /// Creating a `Phasor` instance of 480Hz at 48kHz (phase increment = 0.01):
+    /// ```rust
+    /// # use mydsp_jack::phasor::Phasor;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut phasor4 = Phasor::with_frequency(48000, 480.0);
+    /// debug_assert_eq!(phasor4.tick(0.0), 0.01);
+    /// debug_assert_eq!(phasor4.frequency(), 480.0);
+    /// ```
+    /// # Panics
+    /// The function panics if `sample_rate` is 0, or warns if `frequency` is 0.0.
+    /// ```rust, should_panic
+    /// # use mydsp_jack::phasor::Phasor;
+    /// let phasor_panic = Phasor::with_frequency(0, 440.0);
+    /// ```
+    pub fn with_frequency(sample_rate: usize, frequency: f32) -> Self {
+        if sample_rate == 0 {
+            panic!("!!!Panic: sample_rate must be a positive integer");
+        }
+        let mut phasor = Phasor::new(0.0, frequency / sample_rate as f32);
+        phasor.sample_rate = Some(sample_rate);
+        phasor
+    }
     /// Modifies phase increment.
     /// 
     /// # Examples:
@@ -59,6 +91,63 @@ impl Phasor {
         }
         self.phase_increment = phase_increment;
     }
+    /// Returns the phase increment per tick.
+    pub fn phase_increment(&self) -> f32 {
+        self.phase_increment
+    }
+    /// Modifies the frequency in Hz, can be modulated per sample (no warning at 0Hz, negative frequencies allowed).
+    /// 
+    /// # Examples:
+    /// ```rust
+    /// # use mydsp_jack::phasor::Phasor;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut phasor5 = Phasor::with_frequency(48000, 440.0);
+    /// phasor5.set_frequency(4800.0);
+    /// debug_assert_eq!(phasor5.tick(0.0), 0.1);
+    /// ```
+    /// # Panics
+    /// The function panics if the `Phasor` was not created with `with_frequency()` (unknown sample rate).
+    /// ```rust, should_panic
+    /// # use mydsp_jack::phasor::Phasor;
+    /// let mut phasor_panic = Phasor::new(None, 0.1);
+    /// phasor_panic.set_frequency(440.0);
+    /// ```
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.phase_increment = frequency / self.sample_rate() as f32;
+    }
+    /// Returns the frequency in Hz.
+    /// 
+    /// # Panics
+    /// The function panics if the `Phasor` was not created with `with_frequency()` (unknown sample rate).
+    pub fn frequency(&self) -> f32 {
+        self.phase_increment * self.sample_rate() as f32
+    }
+    /// Returns the current phase (the last value returned by `tick()`).
+    pub fn phase(&self) -> f32 {
+        self.phase
+    }
+    /// Modifies the current phase, wrapped around [0.0, 1.0) (e.g. 1.25 -> 0.25), for phase sync or phase offsets.
+    /// 
+    /// # Examples:
+    /// ```rust
+    /// # use mydsp_jack::phasor::Phasor;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut phasor6 = Phasor::new(None, 0.25);
+    /// phasor6.set_phase(1.25);
+    /// debug_assert_eq!(phasor6.tick(0.0), 0.5);
+    /// phasor6.reset(); // back to the initial phase (0.0)
+    /// debug_assert_eq!(phasor6.tick(0.0), 0.25);
+    /// ```
+    pub fn set_phase(&mut self, phase: f32) {
+        self.phase = phase - phase.floor();
+    }
+    /// Resets the current phase to the initial phase (e.g. to retrigger an oscillator on a new note).
+    pub fn reset(&mut self) {
+        self.phase = self.initial_phase;
+    }
+    fn sample_rate(&self) -> usize {
+        self.sample_rate.unwrap_or_else(|| panic!("!!!Panic: unknown sample rate, create the Phasor with Phasor::with_frequency()"))
+    }
 }
 
 impl AudioComponent for Phasor {
@@ -67,4 +156,14 @@ impl AudioComponent for Phasor {
         self.phase -= self.phase.floor(); // phase wraps around 0 to 1
         self.phase
     }
+    fn process_block(&mut self, input: &[f32], output: &mut [f32]) {
+        crate::check_block(input, output);
+        let mut phase = self.phase;
+        for out_frame in output.iter_mut() {
+            phase += self.phase_increment;
+            phase -= phase.floor();
+            *out_frame = phase;
+        }
+        self.phase = phase;
+    }
 }
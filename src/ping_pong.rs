@@ -0,0 +1,213 @@
+//! The `PingPongDelay` component is a stereo delay with independent left and right times and cross-feedback:
+//! with a mono input (fed to the left channel) and full cross-feedback, the echoes bounce from left to right.
+//!
+//! Per channel: output = delayed frame, written frame = input + feedback * filter(mix of both delayed frames),
+//! where `cross_feedback` in [0.0, 1.0] is the part of the feedback coming from the other channel
+//! (0.0 -> two independent echoes, 1.0 -> ping-pong).
+//! An optional low-pass (`Svf`) in the feedback loop darkens each repeat (tape/analog character).
+//!
+//! Unlike `Echo`, the `dry` and `wet` levels are independent, and the delay times can be synced to a tempo
+//! (`sync_to_tempo()` with `NoteValue`). Changes of delay time glide during `ramp_time` (default 50ms).
+//!
+//! # Examples:
+//! Ping-pong at 1kHz: left 10 frames, right 15 frames, feedback 0.5:
+//! ```rust
+//! # use mydsp_jack::ping_pong::PingPongDelay;
+//! let mut ping_pong1 = PingPongDelay::new(1000, 0.1, 0.01, 0.015, 0.5, 1.0);
+//! ping_pong1.set_dry(0.0);
+//! ping_pong1.set_wet(1.0);
+//! let response: Vec<[f32; 2]> = (0..40).map(|n| ping_pong1.tick_stereo(if n == 0 { 1.0 } else { 0.0 })).collect();
+//! debug_assert_eq!(response[10], [1.0, 0.0]); // first echo on the left
+//! debug_assert_eq!(response[25], [0.0, 0.5]); // then on the right (10 + 15 frames)
+//! debug_assert_eq!(response[35], [0.25, 0.0]); // then on the left again (25 + 10 frames)
+//! let silent = response.iter().enumerate().all(|(n, frame)| [10, 25, 35].contains(&n) || *frame == [0.0, 0.0]);
+//! debug_assert!(silent);
+//! ```
+//! A low-pass in the loop makes the repeats of a 4kHz tone burst decay faster:
+//! ```rust
+//! # use mydsp_jack::ping_pong::PingPongDelay;
+//! # use mydsp_jack::AudioComponent;
+//! fn late_level(cutoff: Option<f32>) -> f32 {
+//!     let mut ping_pong2 = PingPongDelay::new(48000, 0.5, 0.1, 0.15, 0.7, 1.0);
+//!     ping_pong2.set_filter_cutoff(cutoff);
+//!     let mut level = 0.0_f32;
+//!     for n in 0..48000 {
+//!         let window = if n < 480 { 0.5 - 0.5 * (2.0 * std::f32::consts::PI * n as f32 / 480.0).cos() } else { 0.0 }; // Hann
+//!         let in_frame = window * (2.0 * std::f32::consts::PI * 4000.0 * n as f32 / 48000.0).sin();
+//!         let out_frame = ping_pong2.tick(in_frame);
+//!         if n > 24000 { level = level.max(out_frame.abs()); }
+//!     }
+//!     level
+//! }
+//! debug_assert!(late_level(Some(2000.0)) < 0.1 * late_level(None)); // about -38dB
+//! ```
+use crate::AudioComponent;
+use crate::delay_var::DelayVar;
+use crate::multi::{Frame, MultiAudioComponent};
+use crate::svf::{Svf, SvfMode};
+use crate::tempo::NoteValue;
+
+#[derive(Debug)]
+pub struct PingPongDelay {
+    delays: [DelayVar; 2], // [left, right]
+    filters: [Option<Svf>; 2], // low-passes in the feedback loop.
+    sample_rate: usize,
+    max_delay_time: f32,
+    feedback: f32,
+    cross_feedback: f32,
+    dry: f32,
+    wet: f32,
+}
+
+impl PingPongDelay {
+    /// Returns a `PingPongDelay` instance with delays up to `max_delay_time` (seconds), a `left_time` and
+    /// a `right_time` (seconds), a `feedback` in [0.0, 1.0) and a `cross_feedback` in [0.0, 1.0].
+    ///
+    /// Default: dry 1.0, wet 0.5, no filter, ramp time 50ms.
+    ///
+    /// # Panics
+    /// The function panics if a delay time is not in the range [1 / sample_rate, max_delay_time],
+    /// if `feedback` is not in the range [0.0, 1.0) or if `cross_feedback` is not in the range [0.0, 1.0].
+    /// ```rust, should_panic
+    /// # use mydsp_jack::ping_pong::PingPongDelay;
+    /// let ping_pong_panic = PingPongDelay::new(48000, 1.0, 0.25, 2.0, 0.5, 1.0);
+    /// ```
+    pub fn new(sample_rate: usize, max_delay_time: f32, left_time: f32, right_time: f32, feedback: f32, cross_feedback: f32) -> Self {
+        let mut ping_pong = PingPongDelay {
+            delays: std::array::from_fn(|_| DelayVar::with_max_delay_time(sample_rate, max_delay_time)),
+            filters: [None, None],
+            sample_rate,
+            max_delay_time,
+            feedback: 0.0,
+            cross_feedback: 0.0,
+            dry: 1.0,
+            wet: 0.5,
+        };
+        ping_pong.set_times(left_time, right_time);
+        ping_pong.set_ramp_time(0.05);
+        ping_pong.set_feedback(feedback);
+        ping_pong.set_cross_feedback(cross_feedback);
+        ping_pong
+    }
+    /// Modifies the delay times (seconds) of the left and right channels.
+    pub fn set_times(&mut self, left_time: f32, right_time: f32) {
+        for (delay, time) in self.delays.iter_mut().zip([left_time, right_time]) {
+            if time * (self.sample_rate as f32) < 1.0 || time > self.max_delay_time {
+                panic!("!!!PingPongDelay panic: delay times must be in the range [1 / sample_rate, {}]", self.max_delay_time);
+            }
+            // the delayed frame is read before writing the new frame, see `tick_stereo()`.
+            delay.set_delay(time * self.sample_rate as f32 - 1.0);
+        }
+    }
+    /// Sets the delay times to note values at `bpm`, e.g. a dotted eighth on the left and a quarter on the right.
+    ///
+    /// # Examples:
+    /// ```rust
+    /// # use mydsp_jack::ping_pong::PingPongDelay;
+    /// # use mydsp_jack::tempo::NoteValue;
+    /// let mut ping_pong3 = PingPongDelay::new(48000, 2.0, 0.1, 0.1, 0.5, 1.0);
+    /// ping_pong3.set_ramp_time(0.0);
+    /// ping_pong3.sync_to_tempo(120.0, NoteValue::EIGHTH.dotted(), NoteValue::QUARTER);
+    /// debug_assert_eq!(ping_pong3.times(), [0.375, 0.5]);
+    /// ```
+    pub fn sync_to_tempo(&mut self, bpm: f32, left: NoteValue, right: NoteValue) {
+        self.set_times(left.seconds(bpm), right.seconds(bpm));
+    }
+    /// Returns the current delay times (seconds) `[left, right]`, which move toward the last times set during a ramp.
+    pub fn times(&self) -> [f32; 2] {
+        self.delays.each_ref().map(|delay| (delay.delay() + 1.0) / self.sample_rate as f32)
+    }
+    /// Modifies the duration (seconds) of the glide of the next delay times, 0.0 -> immediate.
+    ///
+    /// # Examples:
+    /// Gliding from 10 to 20 frames at 1kHz in 10 frames: on a linear input, the echo is late by `times()` on each frame,
+    /// then an impulse comes back after 20 frames:
+    /// ```rust
+    /// # use mydsp_jack::ping_pong::PingPongDelay;
+    /// let mut ping_pong4 = PingPongDelay::new(1000, 0.1, 0.01, 0.01, 0.0, 0.0);
+    /// ping_pong4.set_dry(0.0);
+    /// ping_pong4.set_wet(1.0);
+    /// ping_pong4.set_ramp_time(0.01);
+    /// (0..20).for_each(|n| { ping_pong4.tick_stereo(n as f32); });
+    /// ping_pong4.set_times(0.02, 0.02);
+    /// for n in 20..30 {
+    ///     let [left, _] = ping_pong4.tick_stereo(n as f32);
+    ///     debug_assert!((left - (n as f32 - 1000.0 * ping_pong4.times()[0])).abs() < 1e-3);
+    /// }
+    /// debug_assert_eq!(ping_pong4.times(), [0.02, 0.02]);
+    /// (30..60).for_each(|_| { ping_pong4.tick_stereo(0.0); });
+    /// let response: Vec<f32> = (0..30).map(|n| ping_pong4.tick_stereo(if n == 0 { 1.0 } else { 0.0 })[0]).collect();
+    /// debug_assert_eq!(response[20], 1.0);
+    /// debug_assert!(response.iter().enumerate().all(|(n, x)| n == 20 || *x == 0.0));
+    /// ```
+    pub fn set_ramp_time(&mut self, ramp_time: f32) {
+        self.delays.iter_mut().for_each(|delay| delay.set_ramp_time(ramp_time));
+    }
+    /// Modifies the feedback in [0.0, 1.0).
+    pub fn set_feedback(&mut self, feedback: f32) {
+        if !(0.0..1.0).contains(&feedback) {
+            panic!("!!!PingPongDelay panic: feedback must be in the range [0.0, 1.0)");
+        }
+        self.feedback = feedback;
+    }
+    /// Modifies the part of the feedback coming from the other channel, in [0.0, 1.0].
+    pub fn set_cross_feedback(&mut self, cross_feedback: f32) {
+        if !(0.0..=1.0).contains(&cross_feedback) {
+            panic!("!!!PingPongDelay panic: cross_feedback must be in the range [0.0, 1.0]");
+        }
+        self.cross_feedback = cross_feedback;
+    }
+    /// Modifies the cutoff frequency (Hz) of the low-pass in the feedback loop, `None` -> no filter.
+    pub fn set_filter_cutoff(&mut self, cutoff: impl Into<Option<f32>>) {
+        let sample_rate = self.sample_rate;
+        match cutoff.into() {
+            Some(cutoff) => {
+                for filter in self.filters.iter_mut() {
+                    filter.get_or_insert_with(|| Svf::new(sample_rate, SvfMode::LowPass, cutoff, std::f32::consts::FRAC_1_SQRT_2))
+                        .set_cutoff(cutoff);
+                }
+            }
+            None => self.filters = [None, None],
+        }
+    }
+    /// Modifies the level of the input.
+    pub fn set_dry(&mut self, dry: f32) {
+        self.dry = dry;
+    }
+    /// Modifies the level of the echoes.
+    pub fn set_wet(&mut self, wet: f32) {
+        self.wet = wet;
+    }
+    /// Processes one stereo frame and returns the stereo output.
+    fn process(&mut self, in_frame: Frame<2>, dry_frame: Frame<2>) -> Frame<2> {
+        // frames delayed by the delay times of this frame (the last written frame is 1 frame old).
+        let delayed: Frame<2> = std::array::from_fn(|channel| self.delays[channel].read_at(self.delays[channel].next_delay()));
+        let mut output = [0.0; 2];
+        for channel in 0..2 {
+            let mut feedback = self.feedback * ((1.0 - self.cross_feedback) * delayed[channel] + self.cross_feedback * delayed[1 - channel]);
+            if let Some(filter) = self.filters[channel].as_mut() {
+                feedback = filter.tick(feedback);
+            }
+            self.delays[channel].tick(in_frame[channel] + feedback);
+            output[channel] = self.dry * dry_frame[channel] + self.wet * delayed[channel];
+        }
+        output
+    }
+    /// Processes one mono frame (fed to the left channel, heard in both channels) and returns `[left, right]`.
+    pub fn tick_stereo(&mut self, in_frame: f32) -> Frame<2> {
+        self.process([in_frame, 0.0], [in_frame, in_frame])
+    }
+}
+
+impl AudioComponent for PingPongDelay {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        let [left, right] = self.tick_stereo(in_frame);
+        0.5 * (left + right)
+    }
+}
+
+impl MultiAudioComponent<2> for PingPongDelay {
+    fn tick_frame(&mut self, in_frame: Frame<2>) -> Frame<2> {
+        self.process(in_frame, in_frame)
+    }
+}
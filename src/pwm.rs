@@ -25,10 +25,10 @@ impl Pwm {
     /// let pwm_panic = Pwm::new(2.0, 0);
     /// ```
     pub fn new(duty_cycle: f32, period: usize) -> Self {
-        if duty_cycle < 0.0 || duty_cycle > 1.0 {
+        if !(0.0..=1.0).contains(&duty_cycle) {
             panic!("!!!Panic: duty_cycle must be in the range [0, 1]");
         }
-        if period <= 0 {
+        if period == 0 {
             panic!("!!!Panic: duty_cycle must be a positive integer");
         }
         Pwm {
@@ -0,0 +1,187 @@
+//! The `Reverb` component is the Freeverb algorithm (Jezar at Dreampoint, a Schroeder-Moorer reverb):
+//! per channel, 8 damped `FeedbackComb` in parallel followed by 4 `Allpass` in series,
+//! the right channel using delay lines 23 frames longer than the left one (stereo spread).
+//!
+//! The delays of Freeverb are tuned for 44100Hz and scaled to the sample rate.
+//!
+//! * `room_size` in [0.0, 1.0]: feedback of the combs (0.7 to 0.98), longer tail.
+//! * `damping` in [0.0, 1.0]: low-pass in the combs, the high frequencies decay faster.
+//! * `width` in [0.0, 1.0]: 0.0 -> mono wet signal, 1.0 -> fully decorrelated left and right.
+//! * `dry` and `wet` in [0.0, 1.0]: levels of the input and of the reverberated signal.
+//!
+//! The input is mono: `tick_stereo()` returns `[left, right]`, `tick()` returns their average,
+//! and `tick_frame()` (`MultiAudioComponent<2>`) reverberates the average of a stereo input.
+//!
+//! **memo**: the allpasses are exact Schroeder allpasses (`Allpass` with a gain of 0.5),
+//! whereas the original Freeverb uses `output = delayed - input`, which is not exactly allpass.
+//!
+//! # Examples:
+//! Impulse response: the wet signal starts after the shortest comb (1116 frames at 44100Hz),
+//! then decays, and a larger room decays more slowly:
+//! ```rust
+//! # use mydsp_jack::reverb::Reverb;
+//! # use mydsp_jack::AudioComponent;
+//! fn impulse_response(reverb: &mut Reverb, nframes: usize) -> Vec<f32> {
+//!     (0..nframes).map(|n| reverb.tick(if n == 0 { 1.0 } else { 0.0 })).collect()
+//! }
+//! let rms = |frames: &[f32]| (frames.iter().map(|x| x * x).sum::<f32>() / frames.len() as f32).sqrt();
+//! let mut small = Reverb::new(44100, 0.2, 0.5, 1.0, 0.0, 1.0);
+//! let mut large = Reverb::new(44100, 0.9, 0.5, 1.0, 0.0, 1.0);
+//! let small_response = impulse_response(&mut small, 88200);
+//! let large_response = impulse_response(&mut large, 88200);
+//! debug_assert!(small_response[..1116].iter().all(|x| *x == 0.0)); // dry = 0.0
+//! debug_assert!(small_response[1116] != 0.0);
+//! // energy of 0.1s windows
+//! let small_early = rms(&small_response[4410..8820]);
+//! let small_late = rms(&small_response[44100..48510]);
+//! let large_late = rms(&large_response[44100..48510]);
+//! debug_assert!(small_late < 0.01 * small_early); // -40dB after 1s
+//! debug_assert!(large_late > 10.0 * small_late);
+//! ```
+//! Deterministic output: the same input gives the same output, and a width of 0.0 gives identical channels:
+//! ```rust
+//! # use mydsp_jack::reverb::Reverb;
+//! let mut reverb1 = Reverb::new(48000, 0.5, 0.5, 1.0, 0.5, 0.5);
+//! let mut reverb2 = Reverb::new(48000, 0.5, 0.5, 1.0, 0.5, 0.5);
+//! let mut reverb_mono = Reverb::new(48000, 0.5, 0.5, 0.0, 0.5, 0.5);
+//! for n in 0..48000 {
+//!     let in_frame = if n % 12000 == 0 { 1.0 } else { 0.0 };
+//!     let [left1, right1] = reverb1.tick_stereo(in_frame);
+//!     debug_assert_eq!([left1, right1], reverb2.tick_stereo(in_frame));
+//!     let [left, right] = reverb_mono.tick_stereo(in_frame);
+//!     debug_assert_eq!(left, right);
+//! }
+//! ```
+use crate::AudioComponent;
+use crate::allpass::Allpass;
+use crate::comb::FeedbackComb;
+use crate::multi::{Frame, MultiAudioComponent};
+
+/// Delays (frames at 44100Hz) of the combs of the left channel.
+const COMB_TUNINGS: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+/// Delays (frames at 44100Hz) of the allpasses of the left channel.
+const ALLPASS_TUNINGS: [usize; 4] = [556, 441, 341, 225];
+/// Extra delay (frames at 44100Hz) of the right channel.
+const STEREO_SPREAD: usize = 23;
+const FIXED_GAIN: f32 = 0.015; // input gain, 8 combs in parallel.
+const SCALE_WET: f32 = 3.0;
+const SCALE_DAMPING: f32 = 0.4;
+const SCALE_ROOM: f32 = 0.28;
+const OFFSET_ROOM: f32 = 0.7;
+const ALLPASS_GAIN: f32 = 0.5;
+
+#[derive(Debug)]
+pub struct Reverb {
+    combs: [[FeedbackComb; 8]; 2], // [left, right]
+    allpasses: [[Allpass; 4]; 2],
+    room_size: f32,
+    damping: f32,
+    width: f32,
+    dry: f32,
+    wet: f32,
+}
+
+impl Reverb {
+    /// Returns a `Reverb` instance, all parameters in [0.0, 1.0].
+    ///
+    /// # Panics
+    /// The function panics if a parameter is not in the range [0.0, 1.0].
+    /// ```rust, should_panic
+    /// # use mydsp_jack::reverb::Reverb;
+    /// let reverb_panic = Reverb::new(48000, 1.5, 0.5, 1.0, 0.5, 0.5);
+    /// ```
+    pub fn new(sample_rate: usize, room_size: f32, damping: f32, width: f32, dry: f32, wet: f32) -> Self {
+        let scale = |frames: usize| ((frames as f32 * sample_rate as f32 / 44100.0).round() as usize).max(1);
+        let spread = |channel: usize| channel * STEREO_SPREAD;
+        let mut reverb = Reverb {
+            combs: std::array::from_fn(|channel| {
+                std::array::from_fn(|i| FeedbackComb::new(scale(COMB_TUNINGS[i] + spread(channel)), 0.0, None))
+            }),
+            allpasses: std::array::from_fn(|channel| {
+                std::array::from_fn(|i| Allpass::new(scale(ALLPASS_TUNINGS[i] + spread(channel)), ALLPASS_GAIN))
+            }),
+            room_size: 0.0,
+            damping: 0.0,
+            width: 0.0,
+            dry: 0.0,
+            wet: 0.0,
+        };
+        reverb.set_room_size(room_size);
+        reverb.set_damping(damping);
+        reverb.set_width(width);
+        reverb.set_dry(dry);
+        reverb.set_wet(wet);
+        reverb
+    }
+    /// Modifies the room size in [0.0, 1.0].
+    pub fn set_room_size(&mut self, room_size: f32) {
+        Self::check_parameter("room_size", room_size);
+        self.room_size = room_size;
+        for comb in self.combs.iter_mut().flatten() {
+            comb.set_feedback(room_size * SCALE_ROOM + OFFSET_ROOM);
+        }
+    }
+    /// Modifies the damping in [0.0, 1.0].
+    pub fn set_damping(&mut self, damping: f32) {
+        Self::check_parameter("damping", damping);
+        self.damping = damping;
+        for comb in self.combs.iter_mut().flatten() {
+            comb.set_damping(damping * SCALE_DAMPING);
+        }
+    }
+    /// Modifies the stereo width in [0.0, 1.0].
+    pub fn set_width(&mut self, width: f32) {
+        Self::check_parameter("width", width);
+        self.width = width;
+    }
+    /// Modifies the level of the input in [0.0, 1.0].
+    pub fn set_dry(&mut self, dry: f32) {
+        Self::check_parameter("dry", dry);
+        self.dry = dry;
+    }
+    /// Modifies the level of the reverberated signal in [0.0, 1.0].
+    pub fn set_wet(&mut self, wet: f32) {
+        Self::check_parameter("wet", wet);
+        self.wet = wet;
+    }
+    /// Clears the combs and the allpasses (silence).
+    pub fn reset(&mut self) {
+        self.combs.iter_mut().flatten().for_each(FeedbackComb::reset);
+        self.allpasses.iter_mut().flatten().for_each(Allpass::reset);
+    }
+    /// Processes one mono frame and returns the stereo frame `[left, right]`.
+    pub fn tick_stereo(&mut self, in_frame: f32) -> Frame<2> {
+        let input = in_frame * FIXED_GAIN;
+        let mut outputs = [0.0; 2];
+        for (output, (combs, allpasses)) in outputs.iter_mut().zip(self.combs.iter_mut().zip(self.allpasses.iter_mut())) {
+            *output = combs.iter_mut().map(|comb| comb.tick(input)).sum();
+            for allpass in allpasses.iter_mut() {
+                *output = allpass.tick(*output);
+            }
+        }
+        let wet1 = self.wet * SCALE_WET * (0.5 + 0.5 * self.width);
+        let wet2 = self.wet * SCALE_WET * (0.5 - 0.5 * self.width);
+        [
+            outputs[0] * wet1 + outputs[1] * wet2 + in_frame * self.dry,
+            outputs[1] * wet1 + outputs[0] * wet2 + in_frame * self.dry,
+        ]
+    }
+    fn check_parameter(name: &str, value: f32) {
+        if !(0.0..=1.0).contains(&value) {
+            panic!("!!!Reverb panic: {} must be in the range [0.0, 1.0]", name);
+        }
+    }
+}
+
+impl AudioComponent for Reverb {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        let [left, right] = self.tick_stereo(in_frame);
+        0.5 * (left + right)
+    }
+}
+
+impl MultiAudioComponent<2> for Reverb {
+    fn tick_frame(&mut self, in_frame: Frame<2>) -> Frame<2> {
+        self.tick_stereo(0.5 * (in_frame[0] + in_frame[1]))
+    }
+}
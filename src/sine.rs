@@ -21,12 +21,11 @@ impl SineWave {
     /// # use mydsp_jack::AudioComponent;
     /// use once_cell::sync::Lazy;
     /// static SINETABLE1: Lazy<SineTable> = Lazy::new(|| SineTable::new(4096));
-    /// let phase_increment = (440/48000) as f32;
-    /// let phasor1 = Phasor::new(None, phase_increment);
-    /// # let phasor2 = Phasor::new(0.49, phase_increment);
+    /// let phasor1 = Phasor::with_frequency(48000, 440.0); // aka. Phasor::new(None, 440.0 / 48000.0)
+    /// # let phasor2 = Phasor::new(0.49, 440.0 / 48000.0);
     /// let sinewave1 = SineWave::new(&SINETABLE1, phasor1);
     /// # let mut sinewave2 = SineWave::new(&SINETABLE1, phasor2);
-    /// # debug_assert_eq!(sinewave2.tick(0.0), 0.06285161);
+    /// # debug_assert_eq!(sinewave2.tick(0.0), 0.006135815); // phase 0.49 + 440/48000
     /// ```
     pub fn new(sine_table: &'static SineTable, phasor: Phasor, ) -> Self {
         SineWave {
@@ -40,4 +39,10 @@ impl AudioComponent for SineWave {
     fn tick(&mut self, in_frame: f32) -> f32 {
         self.sine_table.get_value(self.phasor.tick(in_frame))
     }
+    fn process_block(&mut self, input: &[f32], output: &mut [f32]) {
+        self.phasor.process_block(input, output); // phases first, then lookup in place.
+        for out_frame in output.iter_mut() {
+            *out_frame = self.sine_table.get_value(*out_frame);
+        }
+    }
 }
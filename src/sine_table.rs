@@ -1,15 +1,27 @@
 //! The `SineTable` static component is designed to calculate a sine table only once, for later lookup of sinusoid values.  
 //! 
 //! **memo**: not impl `AudioComponent` trait for its independence, impl `get_value` method instead.  
-//! ## TODO：
-//! * Add linear interpolation while keep using a small table?
-//! * TODO1 line 65.
+//! 
+//! With `Interpolation::Linear` or `Interpolation::Cubic`, a small table (e.g. 256) produces a clean sinusoid. 
 use std::f32::consts::PI;
 // use crate::get_type;
 
+/// How a value is read between two entries of a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// The phase is truncated to an index (the precision depends on the table size).
+    #[default]
+    None,
+    /// Linear interpolation between two entries.
+    Linear,
+    /// Cubic Hermite (Catmull-Rom) interpolation between four entries.
+    Cubic,
+}
+
 #[derive(Debug)]
 pub struct SineTable {
     table: Vec<f32>,
+    interpolation: Interpolation,
 }
 
 impl SineTable {
@@ -25,6 +37,8 @@ impl SineTable {
     /// * 1024 -> 45Hz \| 42Hz
     /// * 2048 -> 22Hz \| 21Hz
     /// * 4096 -> 11Hz \| 11Hz
+    /// 
+    /// These notes are only valid without interpolation, see `with_interpolation()` to use a smaller table.
     /// # Examples:
     /// Creating an instance:
     /// ```rust
@@ -53,17 +67,90 @@ impl SineTable {
             // println!("Number:{2} -- Type:{1} -- Value:{0}", x, get_type(&x), i); // print the whole sine table
             table.push(phase.sin());
         }
-        SineTable { table }
+        SineTable { table, interpolation: Interpolation::None }
+    }
+    /// Returns a `SineTable` instance with a given size, read with a given `interpolation` mode.
+    /// 
+    /// # Examples:
+    /// Creating a small table read with cubic interpolation, as precise as a big table without interpolation:
+    /// ```rust
+    /// # use mydsp_jack::sine_table::{SineTable, Interpolation};
+    /// use once_cell::sync::Lazy;
+    /// static SINETABLE1: Lazy<SineTable> = Lazy::new(|| SineTable::with_interpolation(256, Interpolation::Cubic));
+    /// # let resp1 = SINETABLE1.get_value(0.1);
+    /// # debug_assert!((resp1 - (2.0 * std::f32::consts::PI * 0.1).sin()).abs() < 1e-5);
+    /// ```
+    /// Signal-to-noise ratio compared with `f32::sin` for a table of 64 entries:
+    /// ```rust
+    /// # use mydsp_jack::sine_table::{SineTable, Interpolation};
+    /// fn snr(sinetable: &SineTable) -> f32 {
+    ///     let (mut signal, mut noise) = (0.0_f64, 0.0_f64);
+    ///     for i in 0..10000 {
+    ///         let phase = i as f32 / 10000.0 + 0.123e-4; // between the entries of the table
+    ///         let expected = (2.0 * std::f32::consts::PI * phase).sin();
+    ///         signal += (expected as f64).powi(2);
+    ///         noise += ((sinetable.get_value(phase) - expected) as f64).powi(2);
+    ///     }
+    ///     (10.0 * (signal / noise).log10()) as f32
+    /// }
+    /// let snr_none = snr(&SineTable::with_interpolation(64, Interpolation::None));
+    /// let snr_linear = snr(&SineTable::with_interpolation(64, Interpolation::Linear));
+    /// let snr_cubic = snr(&SineTable::with_interpolation(64, Interpolation::Cubic));
+    /// debug_assert!(snr_none < 30.0);
+    /// debug_assert!(snr_linear > 55.0);
+    /// debug_assert!(snr_cubic > 90.0);
+    /// ```
+    pub fn with_interpolation(size: usize, interpolation: Interpolation) -> SineTable {
+        SineTable { interpolation, ..SineTable::new(size) }
     }
     /// Returns the length of a `SineTable` instance.
     pub fn length(&self) -> usize {
         self.table.len()
     }
+    /// Returns the interpolation mode of a `SineTable` instance.
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
     /// Gets a sinusoid value of a `SineTable` instance using normalized `phase` in range `[0,1)`.
+    /// 
+    /// A `phase` out of `[0,1)` wraps around (e.g. `1.0` -> `0.0`) instead of reading out of the table.
+    /// 
+    /// # Examples:
+    /// ```rust
+    /// # use mydsp_jack::sine_table::SineTable;
+    /// let sinetable1 = SineTable::new(4);
+    /// debug_assert_eq!(sinetable1.get_value(1.0), sinetable1.get_value(0.0));
+    /// debug_assert_eq!(sinetable1.get_value(-0.75), sinetable1.get_value(0.25));
+    /// # debug_assert_eq!(sinetable1.get_value(0.9999999), sinetable1.get_value(0.75));
+    /// ```
     pub fn get_value(&self, phase: f32) -> f32 {
-        let index = (self.length() as f32 * phase) as usize; 
-        // TODO1: should we use this instead when dealing with low percision table? 
-        // let index = if phase >= 1.0 { 0 } else { (self.length() as f32 * phase) as usize };
-        self.table[index]
+        lookup(&self.table, phase, self.interpolation)
+    }
+}
+
+/// Reads `table` at the normalized `phase` (wrapped around [0,1)) with `interpolation`.
+pub(crate) fn lookup(table: &[f32], phase: f32, interpolation: Interpolation) -> f32 {
+    let size = table.len();
+    let position = (phase - phase.floor()) * size as f32;
+    let index = position as usize; 
+    let frac = position - index as f32;
+    let index = index % size; // position can be rounded up to size when phase is close to 1.0.
+    match interpolation {
+        Interpolation::None => table[index],
+        Interpolation::Linear => {
+            let x0 = table[index];
+            let x1 = table[(index + 1) % size];
+            x0 + frac * (x1 - x0)
+        }
+        Interpolation::Cubic => {
+            let xm1 = table[(index + size - 1) % size];
+            let x0 = table[index];
+            let x1 = table[(index + 1) % size];
+            let x2 = table[(index + 2) % size];
+            let c1 = 0.5 * (x1 - xm1);
+            let c2 = xm1 - 2.5 * x0 + 2.0 * x1 - 0.5 * x2;
+            let c3 = 0.5 * (x2 - xm1) + 1.5 * (x0 - x1);
+            ((c3 * frac + c2) * frac + c1) * frac + x0
+        }
     }
 }
@@ -1,23 +1,83 @@
+//! The `Smooth` component is a one-pole low-pass: output = (1 - factor) * input + factor * previous output,
+//! with a smoothing factor in [0.0, 1.0) (0.0 -> no smoothing).
+//!
+//! The factor can be different when the input rises above the output (attack) and when it falls below (release),
+//! e.g. for the `EnvelopeFollower`, and can be computed from a time constant with `Smooth::time_to_factor()`.
+//!
+//! # Examples:
+//! ```rust
+//! # use mydsp_jack::smooth::Smooth;
+//! # use mydsp_jack::AudioComponent;
+//! // fast attack (no smoothing), slow release
+//! let mut smooth1 = Smooth::with_attack_release(0.0, 0.5);
+//! debug_assert_eq!(smooth1.tick(1.0), 1.0);
+//! debug_assert_eq!(smooth1.tick(0.0), 0.5);
+//! debug_assert_eq!(smooth1.tick(0.0), 0.25);
+//! ```
 use crate::AudioComponent;
 
+#[derive(Debug)]
 pub struct Smooth {
     previous_output: f32,
-    smoothing_factor: f32,
+    attack_factor: f32, // smoothing factor when the input is above the previous output.
+    release_factor: f32,
 }
 
 impl Smooth {
     pub fn new(smoothing_factor: f32, ) -> Self {
+        Smooth::with_attack_release(smoothing_factor, smoothing_factor)
+    }
+    /// Returns a `Smooth` instance with a smoothing factor for the rising input (`attack_factor`)
+    /// and another one for the falling input (`release_factor`).
+    pub fn with_attack_release(attack_factor: f32, release_factor: f32) -> Self {
         Smooth {
             previous_output: 0.0,
-            smoothing_factor,
+            attack_factor,
+            release_factor,
+        }
+    }
+    /// Modifies the smoothing factors of the rising and falling input.
+    pub fn set_attack_release(&mut self, attack_factor: f32, release_factor: f32) {
+        self.attack_factor = attack_factor;
+        self.release_factor = release_factor;
+    }
+    /// Returns the smoothing factor of a time constant `time` (seconds): the output reaches 63% (1 - 1 / e)
+    /// of a step after `time`. A time of 0.0 gives the factor 0.0 (no smoothing).
+    ///
+    /// # Examples:
+    /// ```rust
+    /// # use mydsp_jack::smooth::Smooth;
+    /// # use mydsp_jack::AudioComponent;
+    /// let mut smooth2 = Smooth::new(Smooth::time_to_factor(1000, 0.01)); // 10 frames
+    /// let step: Vec<f32> = (0..10).map(|_| smooth2.tick(1.0)).collect();
+    /// debug_assert!((step[9] - (1.0 - (-1.0_f32).exp())).abs() < 1e-5);
+    /// ```
+    /// # Panics
+    /// The function panics if `time` is negative.
+    pub fn time_to_factor(sample_rate: usize, time: f32) -> f32 {
+        if time < 0.0 {
+            panic!("!!!Smooth panic: time must be non-negative");
         }
+        if time == 0.0 {
+            return 0.0;
+        }
+        (-1.0 / (time * sample_rate as f32)).exp()
+    }
+    /// Returns the last output.
+    pub fn value(&self) -> f32 {
+        self.previous_output
+    }
+    /// Clears the previous output.
+    pub fn reset(&mut self) {
+        self.previous_output = 0.0;
     }
 }
 
 impl AudioComponent for Smooth {
     fn tick(&mut self, in_frame: f32) -> f32 {
-        let output = (1.0 - self.smoothing_factor) * in_frame + self.smoothing_factor * self.previous_output;
+        let smoothing_factor = if in_frame > self.previous_output { self.attack_factor } else { self.release_factor };
+        let output = (1.0 - smoothing_factor) * in_frame + smoothing_factor * self.previous_output;
         self.previous_output = output;
         output
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,187 @@
+//! The `Svf` component is a topology-preserving transform (TPT, Zavalishin / Simper) state-variable filter:
+//! a 2-pole filter computing the low-pass, band-pass, high-pass, notch and peak responses at once.
+//!
+//! Unlike the direct forms (e.g. `Biquad`), the state of the integrators does not depend on the coefficients,
+//! so the cutoff can be modulated per sample (LFO, envelope) without smoothing, clicks or instability.
+//!
+//! `tick()` returns the output selected by `SvfMode`, `tick_outputs()` returns all of them.
+//!
+//! # Examples:
+//! Gains at the cutoff frequency (q = 0.707): low-pass, high-pass and band-pass -3dB, notch -inf, peak +3dB:
+//! ```rust
+//! # use mydsp_jack::svf::Svf;
+//! # use mydsp_jack::svf::SvfMode;
+//! let mut svf1 = Svf::new(48000, SvfMode::LowPass, 1000.0, std::f32::consts::FRAC_1_SQRT_2);
+//! let mut amplitudes = [0.0_f32; 5]; // low, band, high, notch, peak
+//! for n in 0..48000 {
+//!     let outputs = svf1.tick_outputs((2.0 * std::f32::consts::PI * 1000.0 * n as f32 / 48000.0).sin());
+//!     if n > 24000 { // steady state
+//!         for (amplitude, output) in amplitudes.iter_mut().zip([outputs.low, outputs.band, outputs.high, outputs.notch, outputs.peak]) {
+//!             *amplitude = amplitude.max(output.abs());
+//!         }
+//!     }
+//! }
+//! for amplitude in &amplitudes[0..3] { debug_assert!((amplitude - 0.707).abs() < 0.005); }
+//! debug_assert!(amplitudes[3] < 0.001);
+//! debug_assert!((amplitudes[4] - 1.414).abs() < 0.01);
+//! ```
+//! Stable under fast modulation: the cutoff sweeps from 20Hz to 20kHz at 200Hz with a high resonance:
+//! ```rust
+//! # use mydsp_jack::svf::Svf;
+//! # use mydsp_jack::svf::SvfMode;
+//! # use mydsp_jack::phasor::Phasor;
+//! # use mydsp_jack::noise::WhiteNoise;
+//! # use mydsp_jack::AudioComponent;
+//! let mut svf2 = Svf::new(48000, SvfMode::BandPass, 1000.0, 20.0);
+//! let mut lfo = Phasor::with_frequency(48000, 200.0);
+//! let mut noise = WhiteNoise::with_seed(1);
+//! let mut max_output = 0.0_f32;
+//! for _ in 0..48000 {
+//!     let lfo_frame = (2.0 * std::f32::consts::PI * lfo.tick(0.0)).sin() * 0.5 + 0.5;
+//!     svf2.set_cutoff(20.0 * 1000.0_f32.powf(lfo_frame)); // exponential sweep
+//!     max_output = max_output.max(svf2.tick(noise.tick(0.0)).abs());
+//! }
+//! debug_assert!(max_output.is_finite() && max_output < 100.0);
+//! ```
+use std::f32::consts::PI;
+use crate::AudioComponent;
+
+/// Output returned by `Svf::tick()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SvfMode {
+    #[default]
+    LowPass,
+    BandPass,
+    HighPass,
+    Notch,
+    /// Low-pass minus high-pass (resonant peak, unity gain far from the cutoff).
+    Peak,
+}
+
+/// The simultaneous outputs of `Svf::tick_outputs()`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SvfOutputs {
+    pub low: f32,
+    pub band: f32,
+    pub high: f32,
+    pub notch: f32,
+    pub peak: f32,
+}
+
+#[derive(Debug)]
+pub struct Svf {
+    mode: SvfMode,
+    sample_rate: usize,
+    cutoff: f32,
+    q: f32,
+    g: f32, // tan(pi * cutoff / sample_rate), integrator gain.
+    k: f32, // 1 / q, damping.
+    a1: f32,
+    a2: f32,
+    a3: f32,
+    ic1eq: f32, // state of the first integrator.
+    ic2eq: f32, // state of the second integrator.
+}
+
+impl Svf {
+    /// Returns an `Svf` instance with a `cutoff` frequency (Hz) and a quality factor `q`
+    /// (0.707 -> Butterworth low-pass/high-pass, higher -> resonance).
+    ///
+    /// # Panics
+    /// The function panics if `q` is not positive (NaN included), `set_q()` too.
+    /// ```rust, should_panic
+    /// # use mydsp_jack::svf::{Svf, SvfMode};
+    /// let svf_panic = Svf::new(48000, SvfMode::LowPass, 1000.0, 0.0);
+    /// ```
+    /// ```rust, should_panic
+    /// # use mydsp_jack::svf::{Svf, SvfMode};
+    /// let mut svf_nan = Svf::new(48000, SvfMode::LowPass, 1000.0, 0.707);
+    /// svf_nan.set_q(f32::NAN);
+    /// ```
+    pub fn new(sample_rate: usize, mode: SvfMode, cutoff: f32, q: f32) -> Self {
+        let mut svf = Svf {
+            mode,
+            sample_rate,
+            cutoff: 0.0,
+            q: 0.0,
+            g: 0.0,
+            k: 0.0,
+            a1: 0.0,
+            a2: 0.0,
+            a3: 0.0,
+            ic1eq: 0.0,
+            ic2eq: 0.0,
+        };
+        svf.set_q(q);
+        svf.set_cutoff(cutoff);
+        svf
+    }
+    /// Modifies the output returned by `tick()`.
+    pub fn set_mode(&mut self, mode: SvfMode) {
+        self.mode = mode;
+    }
+    /// Modifies the cutoff frequency (Hz), clamped to [1, 0.49 * sample_rate] so that it can be modulated per sample.
+    pub fn set_cutoff(&mut self, cutoff: f32) {
+        self.cutoff = cutoff.clamp(1.0, 0.49 * self.sample_rate as f32);
+        self.g = (PI * self.cutoff / self.sample_rate as f32).tan();
+        self.update_coefficients();
+    }
+    /// Modifies the quality factor.
+    pub fn set_q(&mut self, q: f32) {
+        if q.is_nan() || q <= 0.0 {
+            panic!("!!!Svf panic: q must be positive");
+        }
+        self.q = q;
+        self.k = 1.0 / q;
+        self.update_coefficients();
+    }
+    /// Returns the cutoff frequency (Hz), after clamping.
+    pub fn cutoff(&self) -> f32 {
+        self.cutoff
+    }
+    /// Returns the quality factor.
+    pub fn q(&self) -> f32 {
+        self.q
+    }
+    /// Clears the state of the integrators (not the parameters).
+    pub fn reset(&mut self) {
+        self.ic1eq = 0.0;
+        self.ic2eq = 0.0;
+    }
+    /// Processes one frame and returns all the outputs.
+    pub fn tick_outputs(&mut self, in_frame: f32) -> SvfOutputs {
+        let v3 = in_frame - self.ic2eq;
+        let v1 = self.a1 * self.ic1eq + self.a2 * v3;
+        let v2 = self.ic2eq + self.a2 * self.ic1eq + self.a3 * v3;
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+        let low = v2;
+        let band = v1;
+        let high = in_frame - self.k * v1 - v2;
+        SvfOutputs {
+            low,
+            band,
+            high,
+            notch: low + high,
+            peak: low - high,
+        }
+    }
+    fn update_coefficients(&mut self) {
+        self.a1 = 1.0 / (1.0 + self.g * (self.g + self.k));
+        self.a2 = self.g * self.a1;
+        self.a3 = self.g * self.a2;
+    }
+}
+
+impl AudioComponent for Svf {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        let outputs = self.tick_outputs(in_frame);
+        match self.mode {
+            SvfMode::LowPass => outputs.low,
+            SvfMode::BandPass => outputs.band,
+            SvfMode::HighPass => outputs.high,
+            SvfMode::Notch => outputs.notch,
+            SvfMode::Peak => outputs.peak,
+        }
+    }
+}
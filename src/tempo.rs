@@ -0,0 +1,68 @@
+//! Musical time: the `NoteValue` type converts note durations (quarter, dotted eighth, triplet sixteenth...)
+//! to seconds, frames or frequencies at a given tempo (BPM, quarter notes per minute),
+//! to sync delay times and LFO rates.
+//!
+//! # Examples:
+//! ```rust
+//! # use mydsp_jack::tempo::NoteValue;
+//! // at 120 BPM, a quarter note lasts 0.5s
+//! debug_assert_eq!(NoteValue::QUARTER.seconds(120.0), 0.5);
+//! debug_assert_eq!(NoteValue::EIGHTH.dotted().seconds(120.0), 0.375);
+//! debug_assert_eq!(NoteValue::QUARTER.triplet().frames(120.0, 48000), 16000);
+//! debug_assert_eq!(NoteValue::SIXTEENTH.frequency(120.0), 8.0); // LFO rate of one cycle per sixteenth
+//! debug_assert_eq!(NoteValue::new(3, 16).beats(), 0.75);
+//! ```
+
+/// A note duration, stored in beats (quarter notes).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoteValue {
+    beats: f32,
+}
+
+impl NoteValue {
+    pub const WHOLE: NoteValue = NoteValue { beats: 4.0 };
+    pub const HALF: NoteValue = NoteValue { beats: 2.0 };
+    pub const QUARTER: NoteValue = NoteValue { beats: 1.0 };
+    pub const EIGHTH: NoteValue = NoteValue { beats: 0.5 };
+    pub const SIXTEENTH: NoteValue = NoteValue { beats: 0.25 };
+    pub const THIRTY_SECOND: NoteValue = NoteValue { beats: 0.125 };
+
+    /// Returns the duration of `numerator` / `denominator` of a whole note, e.g. (3, 16) = 3 sixteenths.
+    ///
+    /// # Panics
+    /// The function panics if `numerator` or `denominator` is 0.
+    /// ```rust, should_panic
+    /// # use mydsp_jack::tempo::NoteValue;
+    /// let note_panic = NoteValue::new(1, 0);
+    /// ```
+    pub fn new(numerator: u32, denominator: u32) -> Self {
+        if numerator == 0 || denominator == 0 {
+            panic!("!!!Panic: numerator and denominator must be positive");
+        }
+        NoteValue { beats: 4.0 * numerator as f32 / denominator as f32 }
+    }
+    /// Returns the dotted note (1.5 times longer).
+    pub fn dotted(self) -> Self {
+        NoteValue { beats: self.beats * 1.5 }
+    }
+    /// Returns the triplet note (3 in the time of 2).
+    pub fn triplet(self) -> Self {
+        NoteValue { beats: self.beats * 2.0 / 3.0 }
+    }
+    /// Returns the duration in beats (quarter notes).
+    pub fn beats(&self) -> f32 {
+        self.beats
+    }
+    /// Returns the duration in seconds at `bpm`.
+    pub fn seconds(&self, bpm: f32) -> f32 {
+        self.beats * 60.0 / bpm
+    }
+    /// Returns the duration in frames at `bpm` (rounded).
+    pub fn frames(&self, bpm: f32, sample_rate: usize) -> usize {
+        (self.seconds(bpm) * sample_rate as f32).round() as usize
+    }
+    /// Returns the frequency (Hz) of one cycle per note at `bpm`, e.g. to sync a `Phasor`.
+    pub fn frequency(&self, bpm: f32) -> f32 {
+        1.0 / self.seconds(bpm)
+    }
+}
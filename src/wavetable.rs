@@ -0,0 +1,234 @@
+//! The `Wavetable` component generalises `SineTable` to any waveform, built from a function,
+//! a list of harmonic amplitudes or loaded sample data,
+//! and the `WavetableOsc` component plays one or several wavetables.
+//!
+//! `WavetableOsc` morphs between its wavetables with a scan position (0.0 -> first table, 1.0 -> second table, etc.),
+//! and reads band-limited versions of each wavetable (one per octave, mip-mapping) to avoid aliasing at high pitches.
+//!
+//! **memo**: like `SineTable`, `Wavetable` does not impl `AudioComponent` trait, impl `get_value` method instead.
+use std::f32::consts::PI;
+use crate::AudioComponent;
+use crate::phasor::Phasor;
+use crate::sine_table::{lookup, Interpolation};
+
+#[derive(Debug, Clone)]
+pub struct Wavetable {
+    table: Vec<f32>,
+    interpolation: Interpolation,
+}
+
+impl Wavetable {
+    /// Returns a `Wavetable` instance of a given size by calling `f` with the normalized phase `[0,1)` of each entry.
+    ///
+    /// The table is read with `Interpolation::Linear` by default (see `set_interpolation()`).
+    /// # Examples:
+    /// Creating a naive sawtooth:
+    /// ```rust
+    /// # use mydsp_jack::wavetable::Wavetable;
+    /// let saw = Wavetable::from_fn(1024, |phase| 2.0 * phase - 1.0);
+    /// debug_assert_eq!(saw.get_value(0.5), 0.0);
+    /// ```
+    /// # Panics
+    /// The function panics if `size` is 0.
+    /// ```rust, should_panic
+    /// # use mydsp_jack::wavetable::Wavetable;
+    /// let wavetable_panic = Wavetable::from_fn(0, |phase| phase);
+    /// ```
+    pub fn from_fn(size: usize, f: impl Fn(f32) -> f32) -> Self {
+        if size == 0 {
+            panic!("!!!Panic: size of the wavetable should be a positive unsigned integer (usize), not 0.");
+        }
+        Wavetable::from_samples((0..size).map(|i| f(i as f32 / size as f32)).collect())
+    }
+    /// Returns a `Wavetable` instance of a given size by summing sinusoids,
+    /// `amplitudes[0]` is the amplitude of the fundamental, `amplitudes[1]` of the 2nd harmonic, etc.
+    ///
+    /// # Examples:
+    /// Creating a square wave with 4 harmonics (1, 3, 5, 7):
+    /// ```rust
+    /// # use mydsp_jack::wavetable::Wavetable;
+    /// let square = Wavetable::from_harmonics(1024, &[1.0, 0.0, 1.0 / 3.0, 0.0, 1.0 / 5.0, 0.0, 1.0 / 7.0]);
+    /// let sine = Wavetable::from_harmonics(1024, &[1.0]);
+    /// # debug_assert!((sine.get_value(0.25) - 1.0).abs() < 1e-6);
+    /// # debug_assert!((square.get_value(0.25) - (1.0 - 1.0 / 3.0 + 1.0 / 5.0 - 1.0 / 7.0)).abs() < 1e-5);
+    /// ```
+    /// # Panics
+    /// The function panics if `size` is 0.
+    pub fn from_harmonics(size: usize, amplitudes: &[f32]) -> Self {
+        if size == 0 {
+            panic!("!!!Panic: size of the wavetable should be a positive unsigned integer (usize), not 0.");
+        }
+        let (_, sin_table) = trigonometric_tables(size);
+        let mut table = vec![0.0; size];
+        for (n, value) in table.iter_mut().enumerate() {
+            for (h, amplitude) in amplitudes.iter().enumerate() {
+                *value += amplitude * sin_table[((h + 1) * n) % size];
+            }
+        }
+        Wavetable::from_samples(table)
+    }
+    /// Returns a `Wavetable` instance containing one period of loaded sample data.
+    ///
+    /// # Panics
+    /// The function panics if `samples` is empty.
+    pub fn from_samples(samples: Vec<f32>) -> Self {
+        if samples.is_empty() {
+            panic!("!!!Panic: size of the wavetable should be a positive unsigned integer (usize), not 0.");
+        }
+        Wavetable { table: samples, interpolation: Interpolation::Linear }
+    }
+    /// Modifies the interpolation mode used by `get_value()`.
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+    }
+    /// Returns the length of a `Wavetable` instance.
+    pub fn length(&self) -> usize {
+        self.table.len()
+    }
+    /// Gets a value of a `Wavetable` instance using normalized `phase` in range `[0,1)` (wraps around otherwise).
+    pub fn get_value(&self, phase: f32) -> f32 {
+        lookup(&self.table, phase, self.interpolation)
+    }
+    /// Returns the band-limited versions of the wavetable, one per octave:
+    /// the level `k` keeps the harmonics up to `max_harmonic(length, k)`.
+    fn mip_map(&self) -> Vec<Wavetable> {
+        let size = self.length();
+        let (cos_table, sin_table) = trigonometric_tables(size);
+        // Discrete Fourier transform (computed once, not in `tick()`).
+        let dc = crate::add(&self.table) / size as f32;
+        let spectrum: Vec<(f32, f32)> = (1..=max_harmonic(size, 0)).map(|h| {
+            let mut re = 0.0;
+            let mut im = 0.0;
+            for (n, x) in self.table.iter().enumerate() {
+                re += x * cos_table[(h * n) % size];
+                im += x * sin_table[(h * n) % size];
+            }
+            (2.0 * re / size as f32, 2.0 * im / size as f32)
+        }).collect();
+        (0..nb_levels(size)).map(|level| {
+            let nb_harmonics = max_harmonic(size, level);
+            let table = (0..size).map(|n| {
+                let mut value = dc;
+                for (h, (re, im)) in spectrum.iter().take(nb_harmonics).enumerate() {
+                    let index = ((h + 1) * n) % size;
+                    value += re * cos_table[index] + im * sin_table[index];
+                }
+                value
+            }).collect();
+            Wavetable { table, interpolation: self.interpolation }
+        }).collect()
+    }
+}
+
+/// cos and sin of `2 * PI * n / size` for each `n` in `0..size`.
+fn trigonometric_tables(size: usize) -> (Vec<f32>, Vec<f32>) {
+    (0..size).map(|n| {
+        let phase = 2.0 * PI * n as f32 / size as f32;
+        (phase.cos(), phase.sin())
+    }).unzip()
+}
+
+/// Highest harmonic kept by mip-map `level` (one octave lower per level).
+fn max_harmonic(size: usize, level: usize) -> usize {
+    ((size / 2).saturating_sub(1)).max(1) >> level
+}
+
+/// Number of mip-map levels, the last one only keeps the fundamental.
+fn nb_levels(size: usize) -> usize {
+    (0..).take_while(|level| max_harmonic(size, *level) >= 1).count()
+}
+
+#[derive(Debug)]
+pub struct WavetableOsc {
+    mip_maps: Vec<Vec<Wavetable>>, // band-limited versions of each wavetable.
+    phasor: Phasor,
+    sample_rate: usize,
+    level: usize, // mip-map level used for the current frequency.
+    scan_position: f32, // [0, number of wavetables - 1]
+}
+
+impl WavetableOsc {
+    /// Returns a `WavetableOsc` instance playing `wavetables` at a given `frequency` (Hz),
+    /// the band-limited versions of the wavetables are computed here (not real-time safe).
+    ///
+    /// # Examples:
+    /// Creating a band-limited sawtooth oscillator: at 5000Hz, only the harmonics below 24000Hz (Nyquist) are kept.
+    /// ```rust
+    /// # use mydsp_jack::wavetable::{Wavetable, WavetableOsc};
+    /// # use mydsp_jack::AudioComponent;
+    /// let amplitudes: Vec<f32> = (1..=1000).map(|h| 1.0 / h as f32).collect();
+    /// let saw = Wavetable::from_harmonics(2048, &amplitudes);
+    /// let mut osc1 = WavetableOsc::new(48000, vec![saw], 5000.0);
+    /// // harmonics 1, 2, 3 (4 * 5000 < 24000 but levels are octaves: 1023, 511, ..., 7, 3, 1 harmonics)
+    /// let mut expected = WavetableOsc::new(48000, vec![Wavetable::from_harmonics(2048, &amplitudes[..3])], 5000.0);
+    /// for _ in 0..100 {
+    ///     debug_assert!((osc1.tick(0.0) - expected.tick(0.0)).abs() < 1e-3);
+    /// }
+    /// ```
+    /// # Panics
+    /// The function panics if `wavetables` is empty or if the wavetables do not have the same length.
+    /// ```rust, should_panic
+    /// # use mydsp_jack::wavetable::WavetableOsc;
+    /// let osc_panic = WavetableOsc::new(48000, vec![], 440.0);
+    /// ```
+    pub fn new(sample_rate: usize, wavetables: Vec<Wavetable>, frequency: f32) -> Self {
+        if wavetables.is_empty() {
+            panic!("!!!Panic: WavetableOsc needs at least one wavetable");
+        }
+        if wavetables.iter().any(|wavetable| wavetable.length() != wavetables[0].length()) {
+            panic!("!!!Panic: the wavetables of a WavetableOsc must have the same length");
+        }
+        let mut osc = WavetableOsc {
+            mip_maps: wavetables.iter().map(|wavetable| wavetable.mip_map()).collect(),
+            phasor: Phasor::with_frequency(sample_rate, frequency),
+            sample_rate,
+            level: 0,
+            scan_position: 0.0,
+        };
+        osc.set_frequency(frequency);
+        osc
+    }
+    /// Modifies the frequency (Hz), can be modulated per sample.
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.phasor.set_frequency(frequency);
+        // highest harmonic below Nyquist, then the first level which does not exceed it.
+        let max_allowed = (self.sample_rate as f32 / 2.0 / frequency.abs()) as usize;
+        let size = self.mip_maps[0][0].length();
+        let nb_levels = self.mip_maps[0].len();
+        self.level = (0..nb_levels).find(|level| max_harmonic(size, *level) <= max_allowed).unwrap_or(nb_levels - 1);
+    }
+    /// Modifies the scan position between the wavetables, clamped to `[0, number of wavetables - 1]`,
+    /// can be modulated per sample.
+    ///
+    /// # Examples:
+    /// Morphing halfway between a sine and a sine of the opposite phase gives silence:
+    /// ```rust
+    /// # use mydsp_jack::wavetable::{Wavetable, WavetableOsc};
+    /// # use mydsp_jack::AudioComponent;
+    /// let sine = Wavetable::from_harmonics(256, &[1.0]);
+    /// let opposite = Wavetable::from_harmonics(256, &[-1.0]);
+    /// let mut osc2 = WavetableOsc::new(48000, vec![sine, opposite], 440.0);
+    /// osc2.set_scan_position(0.5);
+    /// debug_assert!(osc2.tick(0.0).abs() < 1e-6);
+    /// osc2.set_scan_position(1.0);
+    /// debug_assert!(osc2.tick(0.0) < 0.0);
+    /// ```
+    pub fn set_scan_position(&mut self, scan_position: f32) {
+        self.scan_position = scan_position.clamp(0.0, (self.mip_maps.len() - 1) as f32);
+    }
+}
+
+impl AudioComponent for WavetableOsc {
+    fn tick(&mut self, in_frame: f32) -> f32 {
+        let phase = self.phasor.tick(in_frame);
+        let index = self.scan_position as usize;
+        let frac = self.scan_position - index as f32;
+        let value = self.mip_maps[index][self.level].get_value(phase);
+        if frac > 0.0 {
+            let next_value = self.mip_maps[index + 1][self.level].get_value(phase);
+            value + frac * (next_value - value)
+        } else {
+            value
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Runs a `JackRunner` on a JACK server: attaches to the running server, or starts one with the dummy backend
+//! (`jackd -d dummy`, no sound card needed).
+//!
+//! Needs `jackd` and the `jack` feature: `cargo test --features jack -- --ignored`.
+#![cfg(feature = "jack")]
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use mydsp_jack::AudioComponent;
+use mydsp_jack::gain::Gain;
+use mydsp_jack::jack_runner::JackRunner;
+
+/// The `jackd` process started by the test (killed on drop), `None` if a server was already running.
+struct Server(Option<Child>);
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        if let Some(jackd) = self.0.as_mut() {
+            let _ = jackd.kill();
+            let _ = jackd.wait();
+        }
+    }
+}
+
+/// Returns a client of the running server, or starts `jackd -d dummy` and returns a client of it.
+fn client(name: &str) -> (jack::Client, Server) {
+    if let Ok((client, _status)) = jack::Client::new(name, jack::ClientOptions::NO_START_SERVER) {
+        return (client, Server(None));
+    }
+    let jackd = Command::new("jackd").args(["-d", "dummy", "-r", "48000", "-p", "256"]).spawn()
+        .expect("jackd must be installed to run this test");
+    let server = Server(Some(jackd));
+    let start = Instant::now();
+    loop {
+        if let Ok((client, _status)) = jack::Client::new(name, jack::ClientOptions::NO_START_SERVER) {
+            return (client, server);
+        }
+        assert!(start.elapsed() < Duration::from_secs(5), "jackd -d dummy did not start");
+        sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+#[ignore]
+fn runner_outputs_component_periods() {
+    let (client, _server) = client("mydsp_runner");
+    let (checker, _status) = jack::Client::new("mydsp_checker", jack::ClientOptions::NO_START_SERVER).unwrap();
+    let buffer_size = client.buffer_size() as usize;
+
+    // the component outputs 0.5 on each frame, whatever the input.
+    let builds = Arc::new(AtomicUsize::new(0));
+    let builds_runner = builds.clone();
+    let runner = JackRunner::new(&client, move |_sample_rate, _buffer_size| {
+        builds_runner.fetch_add(1, Ordering::Relaxed);
+        Gain::new(0.5)
+    }).unwrap();
+    assert_eq!(runner.sample_rate(), client.sample_rate());
+    assert_eq!(runner.buffer_size(), buffer_size);
+    let out_port = runner.out_port_name();
+
+    // the checker keeps the last period received from the runner.
+    let checker_in = checker.register_port("in", jack::AudioIn).unwrap();
+    let checker_in_name = checker_in.name().unwrap();
+    let period = Arc::new(Mutex::new(Vec::with_capacity(buffer_size)));
+    let period_process = period.clone();
+    let checker_handler = jack::ClosureProcessHandler::new(move |_: &jack::Client, ps: &jack::ProcessScope| {
+        if let Ok(mut period) = period_process.try_lock() {
+            period.clear();
+            period.extend_from_slice(checker_in.as_slice(ps));
+        }
+        jack::Control::Continue
+    });
+
+    let active_runner = client.activate_async((), runner).unwrap();
+    let active_checker = checker.activate_async((), checker_handler).unwrap();
+    active_runner.as_client().connect_ports_by_name(&out_port, &checker_in_name).unwrap();
+    sleep(Duration::from_millis(500));
+
+    let period = period.lock().unwrap().clone();
+    assert_eq!(period.len(), buffer_size);
+    assert!(period.iter().all(|x| *x == 0.5), "unexpected period: {:?}", period);
+    // built once: the buffer size callback before the first period does not rebuild the component.
+    assert_eq!(builds.load(Ordering::Relaxed), 1);
+
+    active_checker.deactivate().unwrap();
+    let (_client, _notifications, mut runner) = active_runner.deactivate().unwrap();
+    assert_eq!(runner.component_mut().tick(0.0), 0.5);
+}